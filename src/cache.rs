@@ -0,0 +1,319 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use reqwest::header::{HeaderValue, ETAG, IF_NONE_MATCH};
+use reqwest::{Method, Request, StatusCode, Url};
+use tokio::fs;
+
+use crate::client::{read_body, HttpClient};
+use crate::error::CheckerError;
+use crate::release::{parse_data, Release};
+
+/// Fetches and parses the index, reusing the body stored at `cache_path` if
+/// it was written less than `ttl` ago. A missing, stale or unreadable cache
+/// file falls back to a fresh fetch, which then replaces it.
+///
+/// The response's ETag is kept next to the cache file. Once the cache is
+/// stale it is revalidated with `If-None-Match`, and a `304 Not Modified`
+/// reuses the cached body and restarts its `ttl`.
+///
+/// Only an index that parsed is cached. Failing to write the cache doesn't
+/// fail the fetch; the next call simply fetches again.
+pub async fn fetch_releases_cached<C>(
+    url: Url,
+    client: &mut C,
+    cache_path: &Path,
+    ttl: Duration,
+) -> Result<Vec<Release>, CheckerError>
+where
+    C: HttpClient,
+{
+    let cached = read_cached(cache_path).await;
+    if let Some((_, releases)) = &cached {
+        if is_fresh(cache_path, ttl).await {
+            return Ok(releases.clone());
+        }
+    }
+
+    let mut request = Request::new(Method::GET, url.clone());
+    if cached.is_some() {
+        if let Some(etag) = read_etag(cache_path).await {
+            request.headers_mut().insert(IF_NONE_MATCH, etag);
+        }
+    }
+    let response = client.send_request(request).await?;
+    if response.status() == StatusCode::NOT_MODIFIED {
+        // Without a cached body there was no ETag to send, so asking again
+        // would get the same answer.
+        let (body, releases) = cached.ok_or(CheckerError::UnexpectedNotModified)?;
+        report_write_failure(cache_path, fs::write(cache_path, body).await);
+        return Ok(releases);
+    }
+
+    let etag = response.headers().get(ETAG).cloned();
+    let body = read_body(&url, response).await?;
+    let releases = parse_data(&body)?;
+    report_write_failure(cache_path, store(cache_path, &body, etag.as_ref()).await);
+    Ok(releases)
+}
+
+async fn store(cache_path: &Path, body: &str, etag: Option<&HeaderValue>) -> io::Result<()> {
+    // Drop the old ETag first so it can never describe a different body.
+    match fs::remove_file(etag_path(cache_path)).await {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+        _ => {}
+    }
+    fs::write(cache_path, body).await?;
+    if let Some(etag) = etag {
+        fs::write(etag_path(cache_path), etag.as_bytes()).await?;
+    }
+    Ok(())
+}
+
+#[cfg_attr(not(feature = "logging"), allow(unused_variables))]
+fn report_write_failure(cache_path: &Path, result: io::Result<()>) {
+    if let Err(err) = result {
+        #[cfg(feature = "logging")]
+        tracing::warn!(path = %cache_path.display(), %err, "Unable to write index cache");
+    }
+}
+
+async fn read_cached(path: &Path) -> Option<(String, Vec<Release>)> {
+    let body = fs::read_to_string(path).await.ok()?;
+    let releases = parse_data(&body).ok()?;
+    Some((body, releases))
+}
+
+async fn is_fresh(path: &Path, ttl: Duration) -> bool {
+    let modified = match fs::metadata(path).await.and_then(|m| m.modified()) {
+        Ok(modified) => modified,
+        Err(_) => return false,
+    };
+    modified.elapsed().is_ok_and(|age| age <= ttl)
+}
+
+async fn read_etag(cache_path: &Path) -> Option<HeaderValue> {
+    let etag = fs::read(etag_path(cache_path)).await.ok()?;
+    HeaderValue::from_bytes(&etag).ok()
+}
+
+fn etag_path(cache_path: &Path) -> PathBuf {
+    let mut path = cache_path.as_os_str().to_owned();
+    path.push(".etag");
+    PathBuf::from(path)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::FixtureClient;
+    use async_trait::async_trait;
+    use flate2::{write::GzEncoder, Compression};
+    use http::response::Response as HttpResponse;
+    use reqwest::header::CONTENT_ENCODING;
+    use reqwest::{Response, StatusCode};
+    use std::io::Write;
+    use std::str::FromStr;
+
+    struct ConditionalClient {
+        calls: u32,
+        if_none_match: Option<HeaderValue>,
+    }
+
+    #[async_trait]
+    impl HttpClient for ConditionalClient {
+        async fn send_request(&mut self, request: Request) -> Result<Response, CheckerError> {
+            self.calls += 1;
+            self.if_none_match = request.headers().get(IF_NONE_MATCH).cloned();
+            let response = if self.if_none_match.is_some() {
+                HttpResponse::builder()
+                    .status(StatusCode::NOT_MODIFIED)
+                    .body(String::new())
+            } else {
+                HttpResponse::builder()
+                    .status(StatusCode::OK)
+                    .header(ETAG, "\"abc123\"")
+                    .body(std::fs::read_to_string("test/fixtures/index.txt").unwrap())
+            };
+            Ok(Response::from(response.unwrap()))
+        }
+    }
+
+    fn url() -> Url {
+        Url::from_str("https://some.url").unwrap()
+    }
+
+    #[tokio::test]
+    async fn serves_from_cache_within_ttl() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index.txt");
+        let mut client = FixtureClient::index();
+        let ttl = Duration::from_secs(60);
+
+        let first = fetch_releases_cached(url(), &mut client, &path, ttl)
+            .await
+            .unwrap();
+        let second = fetch_releases_cached(url(), &mut client, &path, ttl)
+            .await
+            .unwrap();
+
+        assert_eq!(client.calls(), 1);
+        assert_eq!(first.len(), second.len());
+    }
+
+    #[tokio::test]
+    async fn refetches_after_ttl() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index.txt");
+        let mut client = FixtureClient::index();
+
+        fetch_releases_cached(url(), &mut client, &path, Duration::ZERO)
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        fetch_releases_cached(url(), &mut client, &path, Duration::ZERO)
+            .await
+            .unwrap();
+
+        assert_eq!(client.calls(), 2);
+    }
+
+    #[tokio::test]
+    async fn revalidates_stale_cache_with_etag() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index.txt");
+        let mut client = ConditionalClient {
+            calls: 0,
+            if_none_match: None,
+        };
+
+        fetch_releases_cached(url(), &mut client, &path, Duration::ZERO)
+            .await
+            .unwrap();
+        assert_eq!(client.if_none_match, None);
+        assert_eq!(
+            std::fs::read_to_string(etag_path(&path)).unwrap(),
+            "\"abc123\""
+        );
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let releases = fetch_releases_cached(url(), &mut client, &path, Duration::ZERO)
+            .await
+            .unwrap();
+
+        assert_eq!(client.calls, 2);
+        assert_eq!(
+            client.if_none_match,
+            Some(HeaderValue::from_static("\"abc123\""))
+        );
+        assert_eq!(releases.len(), 9);
+    }
+
+    #[tokio::test]
+    async fn falls_back_on_corrupt_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index.txt");
+        std::fs::write(&path, [0xff, 0xfe, 0x00]).unwrap();
+        let mut client = FixtureClient::index();
+
+        let releases = fetch_releases_cached(url(), &mut client, &path, Duration::from_secs(60))
+            .await
+            .unwrap();
+
+        assert_eq!(client.calls(), 1);
+        assert_eq!(releases.len(), 9);
+    }
+
+    struct BodyClient {
+        body: Vec<u8>,
+        gzip: bool,
+    }
+
+    #[async_trait]
+    impl HttpClient for BodyClient {
+        async fn send_request(&mut self, _request: Request) -> Result<Response, CheckerError> {
+            let mut response = HttpResponse::builder().status(StatusCode::OK);
+            if self.gzip {
+                response = response.header(CONTENT_ENCODING, "gzip");
+            }
+            Ok(Response::from(response.body(self.body.clone()).unwrap()))
+        }
+    }
+
+    #[tokio::test]
+    async fn caches_decoded_gzip_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index.txt");
+        let content = std::fs::read_to_string("test/fixtures/index.txt").unwrap();
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(content.as_bytes()).unwrap();
+        let mut client = BodyClient {
+            body: encoder.finish().unwrap(),
+            gzip: true,
+        };
+
+        let releases = fetch_releases_cached(url(), &mut client, &path, Duration::from_secs(60))
+            .await
+            .unwrap();
+
+        assert_eq!(releases.len(), 9);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), content);
+    }
+
+    #[tokio::test]
+    async fn does_not_cache_unparseable_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index.txt");
+        let mut client = BodyClient {
+            body: b"name\turl\nruby-3.2.2\thttps://example.org".to_vec(),
+            gzip: false,
+        };
+
+        let result =
+            fetch_releases_cached(url(), &mut client, &path, Duration::from_secs(60)).await;
+
+        assert!(matches!(result, Err(CheckerError::MissingColumn(_))));
+        assert!(!path.exists());
+        assert!(!etag_path(&path).exists());
+    }
+
+    #[tokio::test]
+    async fn survives_unwritable_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing").join("index.txt");
+        let mut client = FixtureClient::index();
+
+        let releases = fetch_releases_cached(url(), &mut client, &path, Duration::from_secs(60))
+            .await
+            .unwrap();
+
+        assert_eq!(releases.len(), 9);
+        assert!(!path.exists());
+    }
+
+    struct NotModifiedClient {}
+
+    #[async_trait]
+    impl HttpClient for NotModifiedClient {
+        async fn send_request(&mut self, _request: Request) -> Result<Response, CheckerError> {
+            let response = HttpResponse::builder()
+                .status(StatusCode::NOT_MODIFIED)
+                .body(String::new())
+                .unwrap();
+            Ok(Response::from(response))
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_not_modified_without_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index.txt");
+
+        let result =
+            fetch_releases_cached(url(), &mut NotModifiedClient {}, &path, Duration::ZERO).await;
+
+        assert!(matches!(result, Err(CheckerError::UnexpectedNotModified)));
+        assert!(!path.exists());
+    }
+}
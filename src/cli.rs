@@ -0,0 +1,171 @@
+use std::path::PathBuf;
+
+use clap::{Parser, ValueEnum};
+use ruby_version_checker::{output::Format, release::VersionFilter};
+use semver::Version as SemVerVersion;
+
+#[derive(Debug, Parser)]
+#[command(version)]
+pub struct Cli {
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// Shorthand for `--format json-pretty`
+    #[arg(long, conflicts_with = "format")]
+    pub json_pretty: bool,
+
+    /// Only report versions at or above this version
+    #[arg(long, value_name = "SEMVER")]
+    pub min_version: Option<SemVerVersion>,
+
+    /// Exit with an error if this exact version is missing from the index
+    #[arg(long, value_name = "SEMVER")]
+    pub require: Option<SemVerVersion>,
+
+    /// Major version to report releases for
+    #[arg(long, default_value_t = VersionFilter::default().major)]
+    pub major: u64,
+
+    /// Read the index from a local file instead of fetching it
+    #[arg(long, value_name = "PATH")]
+    pub source_file: Option<PathBuf>,
+
+    /// Read the index from standard input instead of fetching it
+    #[arg(long, conflicts_with = "source_file")]
+    pub stdin: bool,
+
+    /// Print the request that would be sent and exit
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Only print how many versions were found
+    #[arg(long)]
+    pub count: bool,
+
+    /// Print the fetched index as is instead of the parsed releases
+    #[arg(long, conflicts_with_all = ["source_file", "stdin"])]
+    pub raw: bool,
+}
+
+/// The values of `--format`, kept here so the library doesn't depend on clap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Json,
+    JsonPretty,
+    Text,
+    Csv,
+}
+
+impl From<OutputFormat> for Format {
+    fn from(format: OutputFormat) -> Self {
+        match format {
+            OutputFormat::Json => Format::Json,
+            OutputFormat::JsonPretty => Format::JsonPretty,
+            OutputFormat::Text => Format::Text,
+            OutputFormat::Csv => Format::Csv,
+        }
+    }
+}
+
+impl Cli {
+    pub fn output_format(&self) -> Format {
+        match self.json_pretty {
+            true => Format::JsonPretty,
+            false => self.format.into(),
+        }
+    }
+
+    pub fn filter(&self) -> VersionFilter {
+        VersionFilter {
+            major: self.major,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clap::{error::ErrorKind, CommandFactory, FromArgMatches};
+
+    fn parse(args: &[&str]) -> Result<Cli, clap::Error> {
+        let matches = Cli::command().try_get_matches_from(args)?;
+        Cli::from_arg_matches(&matches)
+    }
+
+    #[test]
+    fn defaults_to_text_output() {
+        let cli = parse(&["ruby-version-checker"]).unwrap();
+
+        assert_eq!(cli.output_format(), Format::default());
+        assert_eq!(cli.min_version, None);
+        assert_eq!(cli.source_file, None);
+        assert!(!cli.stdin);
+        assert!(!cli.dry_run);
+        assert!(!cli.count);
+        assert_eq!(cli.filter(), VersionFilter::default());
+    }
+
+    #[test]
+    fn parses_all_flags() {
+        let cli = parse(&[
+            "ruby-version-checker",
+            "--format",
+            "csv",
+            "--min-version",
+            "3.1.0",
+            "--major",
+            "2",
+            "--source-file",
+            "index.txt",
+        ])
+        .unwrap();
+
+        assert_eq!(cli.output_format(), Format::Csv);
+        assert_eq!(cli.min_version, Some("3.1.0".parse().unwrap()));
+        assert_eq!(cli.filter().major, 2);
+        assert_eq!(cli.source_file, Some(PathBuf::from("index.txt")));
+    }
+
+    #[test]
+    fn rejects_stdin_together_with_source_file() {
+        assert!(parse(&["ruby-version-checker", "--stdin"]).unwrap().stdin);
+
+        let err = parse(&[
+            "ruby-version-checker",
+            "--stdin",
+            "--source-file",
+            "index.txt",
+        ])
+        .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn selects_pretty_json() {
+        let cli = parse(&["ruby-version-checker", "--json-pretty"]).unwrap();
+        assert_eq!(cli.output_format(), Format::JsonPretty);
+
+        let cli = parse(&["ruby-version-checker", "--format", "json-pretty"]).unwrap();
+        assert_eq!(cli.output_format(), Format::JsonPretty);
+
+        let err = parse(&["ruby-version-checker", "--json-pretty", "--format", "csv"]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn rejects_invalid_min_version() {
+        let err = parse(&["ruby-version-checker", "--min-version", "3.1"]).unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::ValueValidation);
+        assert_eq!(err.exit_code(), 2);
+    }
+
+    #[test]
+    fn rejects_unknown_format() {
+        let err = parse(&["ruby-version-checker", "--format", "yaml"]).unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::InvalidValue);
+    }
+}
@@ -1,54 +1,744 @@
+use std::io::{Cursor, Read};
+use std::time::Duration;
+
 use async_trait::async_trait;
-use reqwest::{Error, Request, Response};
+use flate2::read::GzDecoder;
+use futures::stream::{self, Stream, StreamExt};
+use reqwest::header::{CONTENT_ENCODING, CONTENT_LENGTH};
+use reqwest::{Error, Method, Request, Response, Url};
+
+use crate::checker::Checker;
+use crate::error::CheckerError;
+use crate::release::{
+    parse_data, parse_data_iter, parse_data_with_filter, DigestAlgorithm, Release,
+};
+
+/// Upper bound on the downloads [`verify_all`] runs at once.
+pub const MAX_CONCURRENT_DOWNLOADS: usize = 4;
+
+pub async fn fetch_data<C>(request: Request, client: &mut C) -> Result<String, CheckerError>
+where
+    C: HttpClient + ?Sized,
+{
+    let url = request.url().clone();
+    let response = client.send_request(request).await?;
+    read_body(&url, response).await
+}
+
+/// Reads the index from the response to a request for `url`, failing on
+/// error statuses. Gzipped indexes are decoded, whether `url` points at a
+/// `.gz` file or the server compressed the response.
+pub(crate) async fn read_body(url: &Url, response: Response) -> Result<String, CheckerError> {
+    #[cfg(feature = "logging")]
+    tracing::debug!(%url, status = %response.status(), "Fetched release index");
+    let gzipped_url = url.path().ends_with(".gz");
+    let response = response.error_for_status()?;
+    let gzip_encoded = response
+        .headers()
+        .get(CONTENT_ENCODING)
+        .is_some_and(|encoding| encoding == "gzip");
+    if !gzipped_url && !gzip_encoded {
+        return Ok(response.text().await?);
+    }
+
+    // reqwest is built without its gzip feature, so decode by hand.
+    let bytes = response.bytes().await?;
+    let mut data = String::new();
+    GzDecoder::new(&bytes[..]).read_to_string(&mut data)?;
+    Ok(data)
+}
+
+/// [`fetch_data`] for clients picked at runtime, e.g. a `Box<dyn HttpClient>`
+/// passed as `client.as_mut()`.
+pub async fn fetch_data_dyn(
+    request: Request,
+    client: &mut dyn HttpClient,
+) -> Result<String, CheckerError> {
+    fetch_data(request, client).await
+}
+
+pub async fn fetch_releases<C>(url: Url, client: &mut C) -> Result<Vec<Release>, CheckerError>
+where
+    C: HttpClient,
+{
+    let data = fetch_data(Request::new(Method::GET, url), client).await?;
+    parse_data(&data)
+}
+
+/// Fetches the index and yields its valid releases as a stream, in the same
+/// way as [`parse_data_iter`]. A failed fetch is yielded as the only item.
+pub fn release_stream<C>(
+    request: Request,
+    client: &mut C,
+) -> impl Stream<Item = Result<Release, CheckerError>> + '_
+where
+    C: HttpClient + Send,
+{
+    stream::once(fetch_data(request, client)).flat_map(|data| match data {
+        Ok(data) => stream::iter(parse_data_iter(Cursor::new(data))).left_stream(),
+        Err(err) => stream::iter([Err(err)]).right_stream(),
+    })
+}
 
-pub async fn fetch_data<C>(request: Request, client: &mut C) -> Result<String, Error>
+pub async fn check_available<C>(url: Url, client: &mut C) -> Result<bool, CheckerError>
 where
     C: HttpClient,
 {
-    client.send_request(request).await.map(|r| r.text())?.await
+    let response = client.send_request(Request::new(Method::HEAD, url)).await?;
+    Ok(response.status().is_success())
+}
+
+/// Asks the mirror for the tarball's size without downloading it. `None`
+/// means the response carried no usable `Content-Length`.
+pub async fn content_length<C>(
+    release: &Release,
+    client: &mut C,
+) -> Result<Option<u64>, CheckerError>
+where
+    C: HttpClient,
+{
+    let url = Url::parse(release.url())
+        .map_err(|_| CheckerError::InvalidUrl(release.url().to_string()))?;
+    let response = client.send_request(Request::new(Method::HEAD, url)).await?;
+    Ok(response
+        .error_for_status()?
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|length| length.to_str().ok())
+        .and_then(|length| length.parse().ok()))
+}
+
+pub async fn fetch_data_with_retry<C>(
+    request: Request,
+    client: &mut C,
+    retries: u32,
+    base_delay: Duration,
+) -> Result<String, CheckerError>
+where
+    C: HttpClient + ?Sized,
+{
+    let mut attempt = 0;
+    loop {
+        match fetch_data(clone_request(&request), client).await {
+            Err(CheckerError::Http(err)) if attempt < retries && is_transient(&err) => {
+                tokio::time::sleep(base_delay.saturating_mul(2u32.saturating_pow(attempt))).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// The index as it was fetched, next to the releases parsed from it.
+#[derive(Debug, Clone)]
+pub struct FetchResult {
+    pub body: String,
+    pub releases: Vec<Release>,
+}
+
+/// Fetches and parses the index configured in `config`, bounding each attempt
+/// by its timeout and retrying transient failures as configured.
+pub async fn fetch_releases_resilient<C>(
+    config: &Checker,
+    client: &mut C,
+) -> Result<Vec<Release>, CheckerError>
+where
+    C: HttpClient + ?Sized,
+{
+    fetch_resilient(config, client)
+        .await
+        .map(|fetched| fetched.releases)
+}
+
+/// Like [`fetch_releases_resilient`], but keeps the raw body around for
+/// inspecting surprising parse results.
+pub async fn fetch_resilient<C>(
+    config: &Checker,
+    client: &mut C,
+) -> Result<FetchResult, CheckerError>
+where
+    C: HttpClient + ?Sized,
+{
+    let mut request = config.request();
+    *request.timeout_mut() = Some(config.timeout());
+    let body =
+        fetch_data_with_retry(request, client, config.retries(), config.retry_delay()).await?;
+    let releases = parse_data_with_filter(&body, config.filter())?;
+    Ok(FetchResult { body, releases })
+}
+
+pub async fn download_and_verify<C>(
+    release: &Release,
+    client: &mut C,
+) -> Result<Vec<u8>, CheckerError>
+where
+    C: HttpClient,
+{
+    download_and_verify_with(release, client, DigestAlgorithm::Sha256).await
+}
+
+/// Like [`download_and_verify`], but checks the download against the given
+/// digest, e.g. the stronger sha512.
+pub async fn download_and_verify_with<C>(
+    release: &Release,
+    client: &mut C,
+    algorithm: DigestAlgorithm,
+) -> Result<Vec<u8>, CheckerError>
+where
+    C: HttpClient,
+{
+    let url = Url::parse(release.url())
+        .map_err(|_| CheckerError::InvalidUrl(release.url().to_string()))?;
+    let response = client.send_request(Request::new(Method::GET, url)).await?;
+    let bytes = response.error_for_status()?.bytes().await?.to_vec();
+
+    release.verify(&bytes, algorithm)?;
+    Ok(bytes)
+}
+
+/// Downloads and verifies every release, with up to
+/// [`MAX_CONCURRENT_DOWNLOADS`] in flight. Each download gets its own clone of
+/// `client`; results are in the same order as `releases`.
+pub async fn verify_all<C>(releases: &[Release], client: &C) -> Vec<Result<(), CheckerError>>
+where
+    C: HttpClient + Clone + Send,
+{
+    stream::iter(releases)
+        .map(|release| {
+            let mut client = client.clone();
+            async move { download_and_verify(release, &mut client).await.map(|_| ()) }
+        })
+        .buffered(MAX_CONCURRENT_DOWNLOADS)
+        .collect()
+        .await
+}
+
+// Requests with a streaming body can't be cloned, but we only ever issue GETs,
+// so rebuilding from the method and URL is enough.
+fn clone_request(request: &Request) -> Request {
+    request
+        .try_clone()
+        .unwrap_or_else(|| Request::new(request.method().clone(), request.url().clone()))
+}
+
+fn is_transient(err: &Error) -> bool {
+    err.is_timeout() || err.is_connect()
 }
 
+/// Sends requests on behalf of the fetch helpers. Implementations report
+/// failures as [`CheckerError`] rather than [`reqwest::Error`] so stacks that
+/// fail outside reqwest, such as `reqwest-middleware`, can be plugged in.
 #[async_trait]
 pub trait HttpClient {
-    async fn send_request(&mut self, request: Request) -> Result<Response, Error>;
+    async fn send_request(&mut self, request: Request) -> Result<Response, CheckerError>;
 }
 
 #[async_trait]
 impl HttpClient for reqwest::Client {
-    async fn send_request(&mut self, request: Request) -> Result<Response, Error> {
-        self.execute(request).await
+    async fn send_request(&mut self, request: Request) -> Result<Response, CheckerError> {
+        Ok(self.execute(request).await?)
+    }
+}
+
+/// Lets requests go through an existing middleware stack, e.g. for tracing
+/// or an organization's retry policy.
+#[cfg(feature = "middleware")]
+#[async_trait]
+impl HttpClient for reqwest_middleware::ClientWithMiddleware {
+    async fn send_request(&mut self, request: Request) -> Result<Response, CheckerError> {
+        Ok(self.execute(request).await?)
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::test_util::FixtureClient;
+    use flate2::{write::GzEncoder, Compression};
     use http::response::Response as HttpResponse;
-    use reqwest::{Method, StatusCode, Url};
+    use reqwest::StatusCode;
+    use sha2::{Digest, Sha256};
+    use std::io::Write;
     use std::{fs, str::FromStr};
 
-    struct MockClient {}
+    #[tokio::test]
+    async fn streams_releases_like_eager_parse() {
+        let mut client = FixtureClient::index();
+        let url = Url::from_str("https://some.url").unwrap();
+        let streamed: Vec<Release> = release_stream(Request::new(Method::GET, url), &mut client)
+            .map(Result::unwrap)
+            .collect()
+            .await;
+
+        let content = fs::read_to_string("test/fixtures/index.txt").unwrap();
+        assert_eq!(streamed, parse_data(&content).unwrap());
+    }
+
+    #[tokio::test]
+    async fn streams_fetch_errors() {
+        let mut client = FlakyClient {
+            failures: 1,
+            calls: 0,
+        };
+        let url = Url::from_str("https://some.url").unwrap();
+        let items: Vec<_> = release_stream(Request::new(Method::GET, url), &mut client)
+            .collect()
+            .await;
+
+        assert_eq!(items.len(), 1);
+        assert!(matches!(&items[0], Err(CheckerError::Http(e)) if e.is_connect()));
+    }
+
+    struct GzipClient {
+        content_encoding: bool,
+    }
 
     #[async_trait]
-    impl HttpClient for MockClient {
-        async fn send_request(&mut self, _request: Request) -> Result<Response, Error> {
-            let content = fs::read_to_string("test/fixtures/index.txt").unwrap();
+    impl HttpClient for GzipClient {
+        async fn send_request(&mut self, _request: Request) -> Result<Response, CheckerError> {
+            let content = fs::read("test/fixtures/index.txt").unwrap();
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(&content).unwrap();
+
+            let mut response = HttpResponse::builder().status(StatusCode::OK);
+            if self.content_encoding {
+                response = response.header(CONTENT_ENCODING, "gzip");
+            }
+            Ok(Response::from(
+                response.body(encoder.finish().unwrap()).unwrap(),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn decompresses_gzip_responses() {
+        let content = fs::read_to_string("test/fixtures/index.txt").unwrap();
+
+        let mut client = GzipClient {
+            content_encoding: true,
+        };
+        let url = Url::from_str("https://some.url/index.txt").unwrap();
+        let data = fetch_data(Request::new(Method::GET, url), &mut client)
+            .await
+            .unwrap();
+        assert_eq!(data, content);
+
+        let mut client = GzipClient {
+            content_encoding: false,
+        };
+        let url = Url::from_str("https://some.url/index.txt.gz").unwrap();
+        let releases = fetch_releases(url, &mut client).await.unwrap();
+        assert_eq!(releases, parse_data(&content).unwrap());
+    }
+
+    struct FlakyClient {
+        failures: u32,
+        calls: u32,
+    }
+
+    #[async_trait]
+    impl HttpClient for FlakyClient {
+        async fn send_request(&mut self, request: Request) -> Result<Response, CheckerError> {
+            self.calls += 1;
+            if self.calls <= self.failures {
+                return Err(connection_error().await.into());
+            }
+            FixtureClient::index().send_request(request).await
+        }
+    }
+
+    async fn connection_error() -> Error {
+        reqwest::Client::new()
+            .get("http://127.0.0.1:1")
+            .send()
+            .await
+            .unwrap_err()
+    }
+
+    #[tokio::test]
+    async fn retries_transient_errors() {
+        let mut client = FlakyClient {
+            failures: 2,
+            calls: 0,
+        };
+        let url = Url::from_str("https://some.url").unwrap();
+        let request = Request::new(Method::GET, url);
+        let data = fetch_data_with_retry(request, &mut client, 3, Duration::from_millis(1))
+            .await
+            .unwrap();
+
+        assert_eq!(client.calls, 3);
+        assert!(data.starts_with("name\turl"));
+    }
+
+    #[tokio::test]
+    async fn returns_last_error_after_exhausting_retries() {
+        let mut client = FlakyClient {
+            failures: 5,
+            calls: 0,
+        };
+        let url = Url::from_str("https://some.url").unwrap();
+        let request = Request::new(Method::GET, url);
+        let result = fetch_data_with_retry(request, &mut client, 2, Duration::from_millis(1)).await;
+
+        assert_eq!(client.calls, 3);
+        assert!(matches!(result, Err(CheckerError::Http(e)) if e.is_connect()));
+    }
+
+    #[tokio::test]
+    async fn resilient_fetch_succeeds_after_transient_failures() {
+        let mut client = FlakyClient {
+            failures: 2,
+            calls: 0,
+        };
+        let checker = Checker::default().with_retries(2, Duration::from_millis(1));
+        let releases = fetch_releases_resilient(&checker, &mut client)
+            .await
+            .unwrap();
+
+        assert_eq!(client.calls, 3);
+        assert!(!releases.is_empty());
+    }
+
+    #[tokio::test]
+    async fn resilient_fetch_fails_once_retries_are_exhausted() {
+        let mut client = FlakyClient {
+            failures: 3,
+            calls: 0,
+        };
+        let checker = Checker::default().with_retries(2, Duration::from_millis(1));
+        let result = fetch_releases_resilient(&checker, &mut client).await;
+
+        assert_eq!(client.calls, 3);
+        assert!(matches!(result, Err(CheckerError::Http(e)) if e.is_connect()));
+    }
+
+    struct BytesClient {
+        body: &'static [u8],
+    }
+
+    #[async_trait]
+    impl HttpClient for BytesClient {
+        async fn send_request(&mut self, _request: Request) -> Result<Response, CheckerError> {
+            let response = HttpResponse::builder()
+                .status(StatusCode::OK)
+                .body(self.body.to_vec())
+                .unwrap();
+            Ok(Response::from(response))
+        }
+    }
+
+    struct StatusClient {
+        status: StatusCode,
+        method: Option<Method>,
+    }
+
+    #[async_trait]
+    impl HttpClient for StatusClient {
+        async fn send_request(&mut self, request: Request) -> Result<Response, CheckerError> {
+            self.method = Some(request.method().clone());
+            let response = HttpResponse::builder()
+                .status(self.status)
+                .body(String::new())
+                .unwrap();
+            Ok(Response::from(response))
+        }
+    }
+
+    #[tokio::test]
+    async fn mirror_is_available_on_success() {
+        let mut client = StatusClient {
+            status: StatusCode::OK,
+            method: None,
+        };
+        let url = Url::from_str("https://some.url").unwrap();
+
+        assert!(check_available(url, &mut client).await.unwrap());
+        assert_eq!(client.method, Some(Method::HEAD));
+    }
+
+    #[tokio::test]
+    async fn fetch_fails_on_server_errors() {
+        let mut client = StatusClient {
+            status: StatusCode::SERVICE_UNAVAILABLE,
+            method: None,
+        };
+        let url = Url::from_str("https://some.url").unwrap();
+        let result = fetch_data(Request::new(Method::GET, url), &mut client).await;
+
+        assert!(matches!(
+            result,
+            Err(CheckerError::Http(err)) if err.status() == Some(StatusCode::SERVICE_UNAVAILABLE)
+        ));
+    }
+
+    #[tokio::test]
+    async fn mirror_is_unavailable_on_not_found() {
+        let mut client = StatusClient {
+            status: StatusCode::NOT_FOUND,
+            method: None,
+        };
+        let url = Url::from_str("https://some.url").unwrap();
+
+        assert!(!check_available(url, &mut client).await.unwrap());
+    }
+
+    struct HeadClient {
+        content_length: Option<u64>,
+        method: Option<Method>,
+    }
+
+    #[async_trait]
+    impl HttpClient for HeadClient {
+        async fn send_request(&mut self, request: Request) -> Result<Response, CheckerError> {
+            self.method = Some(request.method().clone());
+            let mut response = HttpResponse::builder().status(StatusCode::OK);
+            if let Some(length) = self.content_length {
+                response = response.header(CONTENT_LENGTH, length);
+            }
+            Ok(Response::from(response.body(String::new()).unwrap()))
+        }
+    }
+
+    #[tokio::test]
+    async fn reads_content_length_from_head_request() {
+        let release = tarball_release("sha256");
+        let mut client = HeadClient {
+            content_length: Some(20_618_214),
+            method: None,
+        };
+
+        assert_eq!(
+            content_length(&release, &mut client).await.unwrap(),
+            Some(20_618_214)
+        );
+        assert_eq!(client.method, Some(Method::HEAD));
+
+        let mut client = HeadClient {
+            content_length: None,
+            method: None,
+        };
+        assert_eq!(content_length(&release, &mut client).await.unwrap(), None);
+    }
+
+    fn tarball_release(sha256: &str) -> Release {
+        Release::new(
+            "3.2.2".parse().unwrap(),
+            "https://cache.ruby-lang.org/pub/ruby/3.2/ruby-3.2.2.tar.gz".to_string(),
+            sha256.to_string(),
+        )
+    }
+
+    #[tokio::test]
+    async fn downloads_and_verifies_matching_digest() {
+        let mut client = BytesClient {
+            body: b"ruby tarball",
+        };
+        let release =
+            tarball_release("bbdb87a8b8c1e21e697badb730f9fe4b99f5f15a86e29579dfb010da550dfcad");
+        let bytes = download_and_verify(&release, &mut client).await.unwrap();
+
+        assert_eq!(bytes, b"ruby tarball");
+    }
+
+    #[tokio::test]
+    async fn rejects_mismatching_digest() {
+        let mut client = BytesClient {
+            body: b"tampered tarball",
+        };
+        let release =
+            tarball_release("bbdb87a8b8c1e21e697badb730f9fe4b99f5f15a86e29579dfb010da550dfcad");
+        let result = download_and_verify(&release, &mut client).await;
+
+        assert!(matches!(
+            result,
+            Err(CheckerError::ChecksumMismatch { expected, .. }) if expected == release.sha256()
+        ));
+    }
+
+    #[tokio::test]
+    async fn downloads_and_verifies_sha512() {
+        let mut client = BytesClient {
+            body: b"ruby tarball",
+        };
+        let release = Release::builder()
+            .version("3.2.2".parse().unwrap())
+            .sha512("4a7cdc529b545378d3ddd3f45a9bf72c2de352c64624696d14551a626fc698a10b5b56b4397fed30e0b4fe61657a0bd6cb12cfd985118ed30fb0e4f2c93747c2")
+            .build();
+        let bytes = download_and_verify_with(&release, &mut client, DigestAlgorithm::Sha512)
+            .await
+            .unwrap();
+
+        assert_eq!(bytes, b"ruby tarball");
+    }
+
+    #[derive(Clone)]
+    struct EchoClient {}
+
+    #[async_trait]
+    impl HttpClient for EchoClient {
+        async fn send_request(&mut self, request: Request) -> Result<Response, CheckerError> {
             let response = HttpResponse::builder()
                 .status(StatusCode::OK)
-                .body(content)
+                .body(request.url().to_string())
                 .unwrap();
             Ok(Response::from(response))
         }
     }
 
     #[tokio::test]
+    async fn verifies_all_releases_in_order() {
+        let releases: Vec<Release> = ["3.1.4", "3.2.2", "3.3.0"]
+            .iter()
+            .map(|version| {
+                let url = format!(
+                    "https://cache.ruby-lang.org/pub/ruby/{}/ruby-{version}.tar.gz",
+                    &version[..3]
+                );
+                let sha256 = match *version {
+                    "3.2.2" => format!("{:x}", Sha256::digest("tampered")),
+                    _ => format!("{:x}", Sha256::digest(&url)),
+                };
+                Release::new(version.parse().unwrap(), url, sha256)
+            })
+            .collect();
+
+        let results = verify_all(&releases, &EchoClient {}).await;
+
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_ok());
+        assert!(matches!(
+            &results[1],
+            Err(CheckerError::ChecksumMismatch { expected, .. }) if expected == releases[1].sha256()
+        ));
+        assert!(results[2].is_ok());
+    }
+
+    #[tokio::test]
+    #[allow(clippy::unnecessary_first_then_check)]
     async fn fetch_raw_data() {
-        let mut client = MockClient {};
+        let mut client = FixtureClient::index();
         let url = Url::from_str("https://some.url").unwrap();
         let request = Request::new(Method::GET, url);
         let data = fetch_data(request, &mut client).await.unwrap();
-        let releases = crate::release::parse_data(&data).await.unwrap();
+        let releases = crate::release::parse_data(&data).unwrap();
 
         assert!(releases.first().is_some());
     }
+
+    #[tokio::test]
+    async fn fetches_and_parses_releases() {
+        let mut client = FixtureClient::index();
+        let url = Url::from_str("https://some.url").unwrap();
+        let releases = fetch_releases(url, &mut client).await.unwrap();
+
+        assert_eq!(releases.len(), 9);
+        assert_eq!(releases[0].version(), &"3.1.0".parse().unwrap());
+    }
+
+    #[tokio::test]
+    async fn fetch_and_find_latest_versions() {
+        let mut client = FixtureClient::index();
+        let url = Url::from_str("https://some.url").unwrap();
+        let request = Request::new(Method::GET, url);
+        let data = fetch_data(request, &mut client).await.unwrap();
+        let releases = crate::release::parse_data(&data).unwrap();
+        let latest = crate::release::latest_versions(releases);
+
+        assert_eq!(latest.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn keeps_raw_body_next_to_releases() {
+        let mut client = FixtureClient::index();
+        let fetched = fetch_resilient(&Checker::default(), &mut client)
+            .await
+            .unwrap();
+
+        let content = fs::read_to_string("test/fixtures/index.txt").unwrap();
+        assert_eq!(fetched.body, content);
+        assert_eq!(fetched.releases, parse_data(&content).unwrap());
+    }
+
+    #[tokio::test]
+    async fn fetches_through_boxed_client() {
+        let mut client: Box<dyn HttpClient> = Box::new(FixtureClient::index());
+        let url = Url::from_str("https://some.url").unwrap();
+        let data = fetch_data_dyn(Request::new(Method::GET, url), client.as_mut())
+            .await
+            .unwrap();
+
+        let content = fs::read_to_string("test/fixtures/index.txt").unwrap();
+        assert_eq!(data, content);
+    }
+
+    #[cfg(feature = "middleware")]
+    mod middleware {
+        use super::*;
+        use http::Extensions;
+        use reqwest_middleware::{ClientBuilder, Middleware, Next};
+        use std::io;
+
+        /// Answers every request itself instead of passing it on.
+        struct FixtureMiddleware {}
+
+        #[async_trait]
+        impl Middleware for FixtureMiddleware {
+            async fn handle(
+                &self,
+                _request: Request,
+                _extensions: &mut Extensions,
+                _next: Next<'_>,
+            ) -> reqwest_middleware::Result<Response> {
+                let content = fs::read_to_string("test/fixtures/index.txt").unwrap();
+                Ok(Response::from(HttpResponse::new(content)))
+            }
+        }
+
+        struct FailingMiddleware {}
+
+        #[async_trait]
+        impl Middleware for FailingMiddleware {
+            async fn handle(
+                &self,
+                _request: Request,
+                _extensions: &mut Extensions,
+                _next: Next<'_>,
+            ) -> reqwest_middleware::Result<Response> {
+                Err(reqwest_middleware::Error::middleware(io::Error::other(
+                    "policy violation",
+                )))
+            }
+        }
+
+        fn request() -> Request {
+            Request::new(Method::GET, Url::from_str("https://some.url").unwrap())
+        }
+
+        #[tokio::test]
+        async fn fetches_through_middleware_stack() {
+            let mut client = ClientBuilder::new(reqwest::Client::new())
+                .with(FixtureMiddleware {})
+                .build();
+            let releases = fetch_releases_resilient(&Checker::default(), &mut client)
+                .await
+                .unwrap();
+
+            let content = fs::read_to_string("test/fixtures/index.txt").unwrap();
+            assert_eq!(releases, parse_data(&content).unwrap());
+        }
+
+        #[tokio::test]
+        async fn surfaces_middleware_errors() {
+            let mut client = ClientBuilder::new(reqwest::Client::new())
+                .with(FailingMiddleware {})
+                .build();
+            let result = fetch_data(request(), &mut client).await;
+
+            assert!(matches!(
+                result,
+                Err(CheckerError::Middleware(err)) if err.to_string() == "policy violation"
+            ));
+        }
+    }
 }
@@ -0,0 +1,112 @@
+use crate::release::Release;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Upgrade {
+    pub from: Release,
+    pub to: Release,
+}
+
+impl Upgrade {
+    /// See [`Release::is_security_candidate`].
+    pub fn is_security_candidate(&self) -> bool {
+        self.to.is_security_candidate(self.from.version())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReleaseDiff {
+    pub added: Vec<Release>,
+    pub removed: Vec<Release>,
+    pub upgraded: Vec<Upgrade>,
+}
+
+/// Compares two `latest_versions` snapshots series by series: a minor series
+/// only present in `new` is added, one only present in `old` is removed, and
+/// one whose release moved to a higher version is upgraded.
+pub fn diff(old: &[Release], new: &[Release]) -> ReleaseDiff {
+    let mut result = ReleaseDiff::default();
+
+    for release in new {
+        match old.iter().find(|r| same_series(r, release)) {
+            None => result.added.push(release.clone()),
+            Some(previous) if release > previous => result.upgraded.push(Upgrade {
+                from: previous.clone(),
+                to: release.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    for release in old {
+        if !new.iter().any(|r| same_series(r, release)) {
+            result.removed.push(release.clone())
+        }
+    }
+
+    result
+}
+
+fn same_series(a: &Release, b: &Release) -> bool {
+    a.version().major == b.version().major && a.version().minor == b.version().minor
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn release(version: &str) -> Release {
+        Release::new(
+            version.parse().unwrap(),
+            format!("https://cache.ruby-lang.org/pub/ruby/ruby-{version}.tar.gz"),
+            "sha256".to_string(),
+        )
+    }
+
+    #[test]
+    fn reports_upgraded_series() {
+        let old = vec![release("3.1.4"), release("3.2.2")];
+        let new = vec![release("3.1.4"), release("3.2.3")];
+        let diff = diff(&old, &new);
+
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+        assert_eq!(
+            diff.upgraded,
+            vec![Upgrade {
+                from: release("3.2.2"),
+                to: release("3.2.3"),
+            }]
+        );
+    }
+
+    #[test]
+    fn flags_patch_upgrades_as_security_candidates() {
+        let old = vec![release("3.2.2"), release("3.3.0-preview1")];
+        let new = vec![release("3.2.11"), release("3.3.0")];
+        let diff = diff(&old, &new);
+
+        let flagged: Vec<bool> = diff
+            .upgraded
+            .iter()
+            .map(Upgrade::is_security_candidate)
+            .collect();
+        assert_eq!(flagged, vec![true, false]);
+    }
+
+    #[test]
+    fn reports_added_and_removed_series() {
+        let old = vec![release("3.0.6"), release("3.1.4")];
+        let new = vec![release("3.1.4"), release("3.2.0")];
+        let diff = diff(&old, &new);
+
+        assert_eq!(diff.added, vec![release("3.2.0")]);
+        assert_eq!(diff.removed, vec![release("3.0.6")]);
+        assert!(diff.upgraded.is_empty());
+    }
+
+    #[test]
+    fn identical_snapshots_have_no_changes() {
+        let latest = vec![release("3.1.4"), release("3.2.2")];
+
+        assert_eq!(diff(&latest, &latest), ReleaseDiff::default());
+    }
+}
@@ -0,0 +1,91 @@
+use chrono::NaiveDate;
+
+use crate::release::Release;
+
+// (major, minor, year, month, day) as published on
+// https://www.ruby-lang.org/en/downloads/branches/
+const EOL_DATES: &[(u64, u64, i32, u32, u32)] = &[
+    (2, 5, 2021, 4, 5),
+    (2, 6, 2022, 4, 12),
+    (2, 7, 2023, 3, 31),
+    (3, 0, 2024, 4, 23),
+    (3, 1, 2025, 3, 26),
+    (3, 2, 2026, 3, 31),
+];
+
+pub fn eol_date(major: u64, minor: u64) -> Option<NaiveDate> {
+    EOL_DATES
+        .iter()
+        .find(|(ma, mi, ..)| (*ma, *mi) == (major, minor))
+        .and_then(|(_, _, y, m, d)| NaiveDate::from_ymd_opt(*y, *m, *d))
+}
+
+impl Release {
+    /// Returns `None` when the series has no known end-of-life date.
+    pub fn is_eol(&self, today: NaiveDate) -> Option<bool> {
+        eol_date(self.version().major, self.version().minor).map(|eol| today >= eol)
+    }
+}
+
+/// The releases, typically the output of [`latest_versions`], whose series
+/// still receives security fixes on `today`. Series without a known
+/// end-of-life date are newer than the table and count as supported.
+///
+/// [`latest_versions`]: crate::release::latest_versions
+pub fn supported(latest: &[Release], today: NaiveDate) -> Vec<Release> {
+    latest
+        .iter()
+        .filter(|r| r.is_eol(today) != Some(true))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn release(version: &str) -> Release {
+        Release::new(
+            version.parse().unwrap(),
+            format!("https://cache.ruby-lang.org/pub/ruby/ruby-{version}.tar.gz"),
+            "sha256".to_string(),
+        )
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 1, 1).unwrap()
+    }
+
+    #[test]
+    fn flags_eol_series() {
+        assert_eq!(release("3.0.6").is_eol(today()), Some(true));
+        assert_eq!(release("3.1.4").is_eol(today()), Some(false));
+    }
+
+    #[test]
+    fn unknown_series_has_no_eol() {
+        assert_eq!(release("3.9.0").is_eol(today()), None);
+    }
+
+    #[test]
+    fn eol_starts_on_the_published_date() {
+        let eol = eol_date(3, 0).unwrap();
+
+        assert_eq!(release("3.0.6").is_eol(eol), Some(true));
+        assert_eq!(
+            release("3.0.6").is_eol(eol.pred_opt().unwrap()),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn keeps_only_supported_series() {
+        let latest = ["3.0.6", "3.1.4", "3.2.2", "3.3.0"].map(release);
+        let versions: Vec<String> = supported(&latest, today())
+            .iter()
+            .map(|r| r.version().to_string())
+            .collect();
+
+        assert_eq!(versions, ["3.1.4", "3.2.2", "3.3.0"]);
+    }
+}
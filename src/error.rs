@@ -0,0 +1,147 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use semver::Version as SemVerVersion;
+
+use crate::release::DigestAlgorithm;
+
+#[derive(Debug)]
+pub enum CheckerError {
+    Http(reqwest::Error),
+    /// A middleware of a [`reqwest_middleware`] stack failed. Errors of
+    /// the wrapped client are reported as [`CheckerError::Http`].
+    #[cfg(feature = "middleware")]
+    Middleware(reqwest_middleware::Error),
+    Io(io::Error),
+    Csv(csv::Error),
+    Json(serde_json::Error),
+    Toml(toml::de::Error),
+    SemVer(semver::Error),
+    MissingPrefix,
+    InvalidUrl(String),
+    InvalidProxy(String),
+    ChecksumMismatch {
+        expected: String,
+        actual: String,
+    },
+    MalformedChecksum(hex::FromHexError),
+    MissingColumn(String),
+    MissingChecksum(DigestAlgorithm),
+    MissingRelease(SemVerVersion),
+    /// The server answered `304 Not Modified` to a request that had no
+    /// cached index to fall back on.
+    UnexpectedNotModified,
+}
+
+impl fmt::Display for CheckerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckerError::Http(err) => write!(f, "HTTP request failed: {err}"),
+            #[cfg(feature = "middleware")]
+            CheckerError::Middleware(err) => write!(f, "HTTP middleware failed: {err}"),
+            CheckerError::Io(err) => write!(f, "I/O error: {err}"),
+            CheckerError::Csv(err) => write!(f, "Unable to read index data: {err}"),
+            CheckerError::Json(err) => write!(f, "JSON error: {err}"),
+            CheckerError::Toml(err) => write!(f, "Invalid lockfile: {err}"),
+            CheckerError::SemVer(err) => write!(f, "Invalid version: {err}"),
+            CheckerError::MissingPrefix => {
+                write!(f, "Release name is missing the \"ruby-\" prefix")
+            }
+            CheckerError::InvalidUrl(url) => write!(f, "Invalid URL: {url}"),
+            CheckerError::InvalidProxy(proxy) => write!(f, "Invalid proxy URL: {proxy}"),
+            CheckerError::ChecksumMismatch { expected, actual } => {
+                write!(f, "Checksum mismatch: expected {expected}, got {actual}")
+            }
+            CheckerError::MalformedChecksum(err) => write!(f, "Malformed checksum: {err}"),
+            CheckerError::MissingColumn(column) => {
+                write!(f, "Index is missing the \"{column}\" column")
+            }
+            CheckerError::MissingChecksum(algorithm) => {
+                write!(f, "Release has no {algorithm} checksum")
+            }
+            CheckerError::MissingRelease(version) => {
+                write!(f, "Required Ruby release {version} is not in the index")
+            }
+            CheckerError::UnexpectedNotModified => {
+                write!(f, "Server answered 304 Not Modified without a cached index")
+            }
+        }
+    }
+}
+
+impl Error for CheckerError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CheckerError::Http(err) => Some(err),
+            #[cfg(feature = "middleware")]
+            CheckerError::Middleware(err) => Some(err),
+            CheckerError::Io(err) => Some(err),
+            CheckerError::Csv(err) => Some(err),
+            CheckerError::Json(err) => Some(err),
+            CheckerError::Toml(err) => Some(err),
+            CheckerError::SemVer(err) => Some(err),
+            CheckerError::MalformedChecksum(err) => Some(err),
+            CheckerError::MissingPrefix
+            | CheckerError::InvalidUrl(_)
+            | CheckerError::InvalidProxy(_)
+            | CheckerError::ChecksumMismatch { .. }
+            | CheckerError::MissingColumn(_)
+            | CheckerError::MissingChecksum(_)
+            | CheckerError::MissingRelease(_)
+            | CheckerError::UnexpectedNotModified => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for CheckerError {
+    fn from(err: reqwest::Error) -> Self {
+        CheckerError::Http(err)
+    }
+}
+
+#[cfg(feature = "middleware")]
+impl From<reqwest_middleware::Error> for CheckerError {
+    fn from(err: reqwest_middleware::Error) -> Self {
+        match err {
+            reqwest_middleware::Error::Reqwest(err) => CheckerError::Http(err),
+            err => CheckerError::Middleware(err),
+        }
+    }
+}
+
+impl From<io::Error> for CheckerError {
+    fn from(err: io::Error) -> Self {
+        CheckerError::Io(err)
+    }
+}
+
+impl From<csv::Error> for CheckerError {
+    fn from(err: csv::Error) -> Self {
+        CheckerError::Csv(err)
+    }
+}
+
+impl From<serde_json::Error> for CheckerError {
+    fn from(err: serde_json::Error) -> Self {
+        CheckerError::Json(err)
+    }
+}
+
+impl From<toml::de::Error> for CheckerError {
+    fn from(err: toml::de::Error) -> Self {
+        CheckerError::Toml(err)
+    }
+}
+
+impl From<hex::FromHexError> for CheckerError {
+    fn from(err: hex::FromHexError) -> Self {
+        CheckerError::MalformedChecksum(err)
+    }
+}
+
+impl From<semver::Error> for CheckerError {
+    fn from(err: semver::Error) -> Self {
+        CheckerError::SemVer(err)
+    }
+}
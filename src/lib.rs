@@ -0,0 +1,11 @@
+pub mod cache;
+pub mod checker;
+pub mod client;
+pub mod diff;
+pub mod eol;
+pub mod error;
+pub mod output;
+pub mod release;
+pub mod source;
+#[cfg(test)]
+mod test_util;
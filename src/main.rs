@@ -1,31 +1,201 @@
-mod client;
-mod release;
+mod cli;
+#[cfg(test)]
+#[path = "test_util.rs"]
+mod test_util;
 
-use std::{process, str::FromStr};
+use std::io;
+use std::process;
 
-use reqwest::{Client, Method, Request, Url};
+use clap::Parser;
+use ruby_version_checker::{
+    checker::Checker,
+    error::CheckerError,
+    release::{self, Release},
+};
 
-const RELEASE_URL: &str = "https://cache.ruby-lang.org/pub/ruby/index.txt";
+use crate::cli::Cli;
+#[cfg(test)]
+use ruby_version_checker::{client, error};
+
+const EXIT_ERROR: i32 = 1;
+const EXIT_NO_RELEASES: i32 = 3;
+const EXIT_MISSING_REQUIRED: i32 = 4;
 
 #[tokio::main]
 async fn main() {
-    let mut http = Client::builder().https_only(true).build().unwrap();
-    let request = Request::new(Method::GET, Url::from_str(RELEASE_URL).unwrap());
-
-    let csv = client::fetch_data(request, &mut http)
-        .await
-        .expect("Unable to fetch CSV data from the Ruby server");
-
-    let releases = match release::parse_data(&csv).await {
-        Ok(r) => r,
-        Err(err) => {
-            println!("Error parsing data: {err}");
-            process::exit(1);
-        }
+    let cli = Cli::parse();
+
+    #[cfg(feature = "logging")]
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .init();
+
+    process::exit(run(&cli).await);
+}
+
+/// Runs the checker and returns the process exit code: 0 when releases were
+/// found, [`EXIT_ERROR`] on failure and [`EXIT_NO_RELEASES`] when the index
+/// had no matching releases, which usually means its format changed.
+/// [`EXIT_MISSING_REQUIRED`] means the version passed to `--require` is gone.
+async fn run(cli: &Cli) -> i32 {
+    let mut checker = checker(cli);
+    let latest_versions = match latest_releases(cli, &mut checker).await {
+        Ok(Some(latest)) => latest,
+        Ok(None) => return 0,
+        Err(err) => return report_error(err),
     };
+    if latest_versions.is_empty() {
+        eprintln!("Warning: no matching Ruby releases found");
+        return EXIT_NO_RELEASES;
+    }
+
+    println!("{}", render(cli, &checker, &latest_versions).trim_end());
+    0
+}
+
+fn checker(cli: &Cli) -> Checker {
+    let mut checker = Checker::default()
+        .with_filter(cli.filter())
+        .with_format(cli.output_format())
+        .with_dry_run(cli.dry_run);
+    if let Some(floor) = &cli.min_version {
+        checker = checker.with_min_version(floor.clone());
+    }
+    if let Some(required) = &cli.require {
+        checker = checker.with_required(required.clone());
+    }
+    checker
+}
+
+fn render(cli: &Cli, checker: &Checker, latest_versions: &[Release]) -> String {
+    if cli.count {
+        return latest_versions.len().to_string();
+    }
+    checker.format().formatter().format(latest_versions)
+}
+
+/// The latest releases of the index `cli` points at. `None` means there is
+/// nothing left to report, because this was a dry run or `--raw` already
+/// printed the index.
+async fn latest_releases(
+    cli: &Cli,
+    checker: &mut Checker,
+) -> Result<Option<Vec<Release>>, CheckerError> {
+    if cli.stdin {
+        let releases = release::parse_reader_with_filter(io::stdin().lock(), checker.filter())?;
+        return checker.select(releases).map(Some);
+    }
+    if let Some(path) = &cli.source_file {
+        let releases = release::parse_file_with_filter(path, checker.filter())?;
+        return checker.select(releases).map(Some);
+    }
+    fetch_latest(checker, cli.raw).await
+}
+
+async fn fetch_latest(
+    checker: &mut Checker,
+    raw: bool,
+) -> Result<Option<Vec<Release>>, CheckerError> {
+    if checker.dry_run() {
+        println!("{}", checker.describe_request());
+        return Ok(None);
+    }
+    if raw {
+        print!("{}", checker.fetch().await?.body);
+        return Ok(None);
+    }
+    checker.run().await.map(Some)
+}
+
+/// Writes `err` to stderr, away from the releases on stdout, and returns its
+/// exit code.
+fn report_error(err: CheckerError) -> i32 {
+    eprintln!("Error: {err}");
+    match err {
+        CheckerError::MissingRelease(_) => EXIT_MISSING_REQUIRED,
+        _ => EXIT_ERROR,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::FixtureClient;
+    use std::path::Path;
+
+    #[tokio::test]
+    async fn exits_with_distinct_code_without_releases() {
+        let cli = Cli::parse_from([
+            "ruby-version-checker",
+            "--source-file",
+            "test/fixtures/index.txt",
+        ]);
+        assert_eq!(run(&cli).await, 0);
+
+        let cli = Cli::parse_from([
+            "ruby-version-checker",
+            "--source-file",
+            "test/fixtures/index.txt",
+            "--major",
+            "9",
+        ]);
+        assert_eq!(run(&cli).await, EXIT_NO_RELEASES);
+
+        let cli = Cli::parse_from([
+            "ruby-version-checker",
+            "--source-file",
+            "test/fixtures/missing.txt",
+        ]);
+        assert_eq!(run(&cli).await, EXIT_ERROR);
+    }
+
+    #[tokio::test]
+    async fn fails_when_required_version_is_missing() {
+        let cli = |version| {
+            Cli::parse_from([
+                "ruby-version-checker",
+                "--source-file",
+                "test/fixtures/index.txt",
+                "--require",
+                version,
+            ])
+        };
+
+        assert_eq!(run(&cli("3.2.2")).await, 0);
+        assert_eq!(run(&cli("3.2.99")).await, EXIT_MISSING_REQUIRED);
+    }
+
+    #[test]
+    fn renders_only_the_count() {
+        let cli = Cli::parse_from(["ruby-version-checker", "--count"]);
+        let releases = release::parse_file(Path::new("test/fixtures/index.txt")).unwrap();
+        let latest = release::latest_versions(releases);
+
+        assert_eq!(render(&cli, &checker(&cli), &latest), "3");
+    }
+
+    #[tokio::test]
+    async fn dry_run_sends_no_request() {
+        let client = FixtureClient::index();
+        let mut checker = Checker::default()
+            .with_dry_run(true)
+            .with_client(client.clone());
+
+        assert!(fetch_latest(&mut checker, false).await.unwrap().is_none());
+        assert_eq!(client.calls(), 0);
+
+        let mut checker = checker.with_dry_run(false);
+        assert!(fetch_latest(&mut checker, false).await.unwrap().is_some());
+        assert_eq!(client.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn raw_prints_body_instead_of_releases() {
+        let client = FixtureClient::index();
+        let mut checker = Checker::default().with_client(client.clone());
 
-    let latest_versions = release::latest_versions(releases).await;
-    let json = serde_json::to_string_pretty(&latest_versions)
-        .expect("Unable to serialize releases into JSON structure");
-    println!("{json}")
+        assert!(fetch_latest(&mut checker, true).await.unwrap().is_none());
+        assert_eq!(client.calls(), 1);
+    }
 }
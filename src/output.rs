@@ -0,0 +1,311 @@
+use std::collections::BTreeMap;
+
+use semver::Version as SemVerVersion;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::error::CheckerError;
+use crate::release::{self, Release};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    Json,
+    JsonPretty,
+    #[default]
+    Text,
+    Csv,
+}
+
+impl Format {
+    pub fn formatter(&self) -> Box<dyn OutputFormatter> {
+        match self {
+            Format::Json => Box::new(JsonFormatter),
+            Format::JsonPretty => Box::new(PrettyJsonFormatter),
+            Format::Text => Box::new(TextFormatter),
+            Format::Csv => Box::new(CsvFormatter),
+        }
+    }
+
+    pub fn render(&self, releases: &[Release]) -> String {
+        self.formatter().format(releases)
+    }
+}
+
+/// Turns a list of releases into one of the output formats.
+pub trait OutputFormatter {
+    fn format(&self, releases: &[Release]) -> String;
+}
+
+pub struct JsonFormatter;
+
+impl OutputFormatter for JsonFormatter {
+    fn format(&self, releases: &[Release]) -> String {
+        to_json(releases).expect("releases always serialize to JSON")
+    }
+}
+
+pub struct PrettyJsonFormatter;
+
+impl OutputFormatter for PrettyJsonFormatter {
+    fn format(&self, releases: &[Release]) -> String {
+        to_json_pretty(releases).expect("releases always serialize to JSON")
+    }
+}
+
+pub struct TextFormatter;
+
+impl OutputFormatter for TextFormatter {
+    fn format(&self, releases: &[Release]) -> String {
+        to_text(releases)
+    }
+}
+
+pub struct CsvFormatter;
+
+impl OutputFormatter for CsvFormatter {
+    fn format(&self, releases: &[Release]) -> String {
+        to_csv(releases).expect("releases always serialize to CSV")
+    }
+}
+
+pub fn to_json(releases: &[Release]) -> Result<String, serde_json::Error> {
+    serde_json::to_string(releases)
+}
+
+/// Same as [`to_json`], indented for humans.
+pub fn to_json_pretty(releases: &[Release]) -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(releases)
+}
+
+pub fn to_actions_matrix(releases: &[Release]) -> String {
+    let mut versions: Vec<_> = releases.iter().map(|r| r.version()).collect();
+    versions.sort_by_key(|v| (v.major, v.minor));
+    let include: Vec<_> = versions
+        .iter()
+        .map(|v| json!({ "ruby": v.to_string() }))
+        .collect();
+    json!({ "include": include }).to_string()
+}
+
+/// One `RUBY_<major>_<minor>=<version>` line per minor, ordered by minor.
+/// When several releases share a minor only the newest is exported.
+pub fn to_shell_exports(releases: &[Release]) -> String {
+    let mut newest: BTreeMap<(u64, u64), &SemVerVersion> = BTreeMap::new();
+    for version in releases.iter().map(|r| r.version()) {
+        let entry = newest
+            .entry((version.major, version.minor))
+            .or_insert(version);
+        if version > *entry {
+            *entry = version;
+        }
+    }
+    newest
+        .into_iter()
+        .map(|((major, minor), version)| format!("RUBY_{major}_{minor}={version}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A `.tool-versions` line for asdf, e.g. `ruby 3.2.2`.
+pub fn to_asdf(release: &Release) -> String {
+    format!("ruby {}", release.install_name())
+}
+
+/// The `.tool-versions` line for the newest valid release of `minor`.
+pub fn to_asdf_for_minor(releases: &[Release], minor: u64) -> Option<String> {
+    release::latest_for_minor(releases.to_vec(), minor).map(|r| to_asdf(&r))
+}
+
+pub fn to_text(releases: &[Release]) -> String {
+    releases
+        .iter()
+        .map(|r| r.version().to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn to_csv(releases: &[Release]) -> Result<String, csv::Error> {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    for release in releases {
+        writer.serialize(release)?;
+    }
+    let bytes = writer.into_inner().map_err(|err| err.into_error())?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[derive(Serialize, Deserialize)]
+struct Lockfile {
+    release: Vec<LockedRelease>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LockedRelease {
+    version: SemVerVersion,
+    url: String,
+    sha256: String,
+}
+
+/// Pins `releases` in a TOML lockfile with one `[[release]]` table each.
+/// Read it back with [`from_toml_lock`].
+pub fn to_toml_lock(releases: &[Release]) -> String {
+    let lockfile = Lockfile {
+        release: releases
+            .iter()
+            .map(|r| LockedRelease {
+                version: r.version().clone(),
+                url: r.url().to_string(),
+                sha256: r.sha256().to_string(),
+            })
+            .collect(),
+    };
+    toml::to_string(&lockfile).expect("a lockfile of strings always serializes")
+}
+
+pub fn from_toml_lock(lockfile: &str) -> Result<Vec<Release>, CheckerError> {
+    let lockfile: Lockfile = toml::from_str(lockfile)?;
+    Ok(lockfile
+        .release
+        .into_iter()
+        .map(|r| Release::new(r.version, r.url, r.sha256))
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::release::{latest_versions, parse_data};
+
+    use std::fs;
+
+    #[test]
+    fn renders_text_and_csv() {
+        let content = fs::read_to_string("test/fixtures/index.txt").unwrap();
+        let releases = latest_versions(parse_data(&content).unwrap());
+
+        assert_eq!(Format::Text.render(&releases), "3.1.4\n3.2.2\n3.3.0");
+
+        let csv = Format::Csv.render(&releases);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], "version,url,sha256");
+        assert!(lines[2]
+            .starts_with("3.2.2,https://cache.ruby-lang.org/pub/ruby/3.2/ruby-3.2.2.tar.gz,"));
+    }
+
+    #[test]
+    fn formatters_match_their_format() {
+        let content = fs::read_to_string("test/fixtures/index.txt").unwrap();
+        let releases = latest_versions(parse_data(&content).unwrap());
+
+        assert_eq!(JsonFormatter.format(&releases), to_json(&releases).unwrap());
+        assert_eq!(TextFormatter.format(&releases), "3.1.4\n3.2.2\n3.3.0");
+        assert!(CsvFormatter
+            .format(&releases)
+            .starts_with("version,url,sha256\n3.1.4,"));
+
+        for format in [Format::Json, Format::JsonPretty, Format::Text, Format::Csv] {
+            assert_eq!(
+                format.formatter().format(&releases),
+                format.render(&releases)
+            );
+        }
+    }
+
+    #[test]
+    fn renders_actions_matrix() {
+        let content = fs::read_to_string("test/fixtures/index.txt").unwrap();
+        let mut releases = latest_versions(parse_data(&content).unwrap());
+        releases.reverse();
+
+        assert_eq!(
+            to_actions_matrix(&releases),
+            r#"{"include":[{"ruby":"3.1.4"},{"ruby":"3.2.2"},{"ruby":"3.3.0"}]}"#
+        );
+    }
+
+    #[test]
+    fn renders_shell_exports() {
+        let content = fs::read_to_string("test/fixtures/index.txt").unwrap();
+        let releases = latest_versions(parse_data(&content).unwrap());
+
+        assert_eq!(
+            to_shell_exports(&releases),
+            "RUBY_3_1=3.1.4\nRUBY_3_2=3.2.2\nRUBY_3_3=3.3.0"
+        );
+    }
+
+    #[test]
+    fn exports_newest_release_per_minor() {
+        let releases: Vec<_> = ["3.2.1", "3.3.0", "3.2.2", "3.2.0"]
+            .iter()
+            .map(|v| Release::new(v.parse().unwrap(), String::new(), String::new()))
+            .collect();
+
+        assert_eq!(
+            to_shell_exports(&releases),
+            "RUBY_3_2=3.2.2\nRUBY_3_3=3.3.0"
+        );
+    }
+
+    #[test]
+    fn json_round_trips() {
+        let content = fs::read_to_string("test/fixtures/index.txt").unwrap();
+        let releases = latest_versions(parse_data(&content).unwrap());
+
+        let json = to_json(&releases).unwrap();
+        assert!(json.contains(r#""version":"3.2.2""#));
+
+        let parsed: Vec<Release> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.len(), releases.len());
+        assert!(parsed.iter().zip(&releases).all(|(p, r)| p.strict_eq(r)));
+    }
+
+    #[test]
+    fn pretty_json_round_trips() {
+        let content = fs::read_to_string("test/fixtures/index.txt").unwrap();
+        let releases = latest_versions(parse_data(&content).unwrap());
+
+        let pretty = to_json_pretty(&releases).unwrap();
+        assert!(pretty.contains("\n  {\n    \"version\": \"3.1.4\""));
+
+        let parsed: Vec<Release> = serde_json::from_str(&pretty).unwrap();
+        assert_eq!(parsed.len(), releases.len());
+        assert!(parsed.iter().zip(&releases).all(|(p, r)| p.strict_eq(r)));
+    }
+
+    #[test]
+    fn round_trips_toml_lock() {
+        let content = fs::read_to_string("test/fixtures/index.txt").unwrap();
+        let releases = latest_versions(parse_data(&content).unwrap());
+        let lockfile = to_toml_lock(&releases);
+
+        assert!(lockfile.starts_with(
+            "[[release]]\nversion = \"3.1.4\"\nurl = \"https://cache.ruby-lang.org/pub/ruby/3.1/ruby-3.1.4.tar.gz\"\n"
+        ));
+        let parsed = from_toml_lock(&lockfile).unwrap();
+        assert_eq!(parsed.len(), releases.len());
+        assert!(parsed.iter().zip(&releases).all(|(p, r)| p.strict_eq(r)));
+
+        assert!(matches!(
+            from_toml_lock("[[release]]\nversion = \"3.2\""),
+            Err(CheckerError::Toml(_))
+        ));
+    }
+
+    #[test]
+    fn renders_asdf_tool_versions() {
+        let content = fs::read_to_string("test/fixtures/index.txt").unwrap();
+        let releases = parse_data(&content).unwrap();
+        let release = releases
+            .iter()
+            .find(|r| r.version().to_string() == "3.2.2")
+            .unwrap();
+
+        assert_eq!(to_asdf(release), "ruby 3.2.2");
+        assert_eq!(
+            to_asdf_for_minor(&releases, 1).as_deref(),
+            Some("ruby 3.1.4")
+        );
+        assert_eq!(to_asdf_for_minor(&releases, 9), None);
+    }
+}
@@ -1,23 +1,64 @@
+use std::borrow::Cow;
 use std::cmp::Ordering;
-use std::error::Error;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io::Read;
 use std::ops::Range;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::OnceLock;
 
-use csv::ReaderBuilder;
+use chrono::{Datelike, NaiveDate};
+use csv::{ReaderBuilder, StringRecord, Trim};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use regex::Regex;
-use semver::Version as SemVerVersion;
+use semver::{BuildMetadata, Version as SemVerVersion, VersionReq};
 use serde::de::Error as SerdeError;
 use serde::{Deserialize, Deserializer, Serialize};
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::error::CheckerError;
 
 const MINOR_RANGE: Range<u64> = 1..99;
 const PATCH_RANGE: Range<u64> = 0..99;
+const DEFAULT_MAJOR: u64 = 3;
+
+const SHA256_PATTERN: &str = r"^[0-9a-f]{64}$";
+const ARCHIVE_URL_PATTERN: &str = r"https://.*\.(tar\.gz|tar\.xz|tar\.bz2|zip)$";
+const URL_VERSION_PATTERN: &str = r"/ruby-([^/]+?)\.(?:tar\.gz|tar\.xz|tar\.bz2|zip)$";
+const NEWS_URL: &str = "https://www.ruby-lang.org/en/news/";
+
+/// The digest [`ReleaseBuilder`] uses when none is set. It is well-formed,
+/// but matches no real archive.
+pub const PLACEHOLDER_SHA256: &str =
+    "0000000000000000000000000000000000000000000000000000000000000000";
+
+// Validation runs for every row of the index, so each pattern is compiled
+// once on first use rather than per call.
+static SHA256: OnceLock<Regex> = OnceLock::new();
+static ARCHIVE_URL: OnceLock<Regex> = OnceLock::new();
+static URL_VERSION: OnceLock<Regex> = OnceLock::new();
+
+fn cached(cell: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+    cell.get_or_init(|| Regex::new(pattern).unwrap())
+}
 
 #[derive(Debug, Serialize, Deserialize, Eq, Clone)]
 pub struct Release {
-    #[serde(rename = "name")]
+    #[serde(rename(serialize = "version", deserialize = "name"), alias = "version")]
     #[serde(deserialize_with = "parse_semver_version")]
     version: SemVerVersion,
     url: String,
     sha256: String,
+    /// Only used for verifying downloads, so it's left out of the output.
+    #[serde(default, skip_serializing)]
+    sha512: Option<String>,
+    /// Not part of the index; see [`crate::source::enrich_dates`].
+    #[serde(skip)]
+    released_on: Option<NaiveDate>,
 }
 
 fn parse_semver_version<'de, D>(deserializer: D) -> Result<SemVerVersion, D::Error>
@@ -25,8 +66,95 @@ where
     D: Deserializer<'de>,
 {
     let version: String = String::deserialize(deserializer)?;
-    let version = version.strip_prefix("ruby-").unwrap();
-    version.parse().map_err(D::Error::custom)
+    version
+        .parse::<RubyVersion>()
+        .map(|v| v.0)
+        .map_err(D::Error::custom)
+}
+
+pub fn parse_version(name: &str) -> Result<SemVerVersion, CheckerError> {
+    Ok(name.parse::<RubyVersion>()?.0)
+}
+
+/// A version of MRI, parsed from either `3.2.2` or `ruby-3.2.2`. Names with
+/// any other prefix, such as `jruby-9.4.0.0`, are rejected as
+/// [`CheckerError::MissingPrefix`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RubyVersion(pub SemVerVersion);
+
+impl FromStr for RubyVersion {
+    type Err = CheckerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let version = s.strip_prefix("ruby-").unwrap_or(s);
+        if !version.starts_with(|c: char| c.is_ascii_digit()) {
+            return Err(CheckerError::MissingPrefix);
+        }
+        Ok(Self(version.parse()?))
+    }
+}
+
+impl fmt::Display for RubyVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ruby-{}", self.0)
+    }
+}
+
+/// Selects which releases are considered valid, and how the index
+/// listing them is split into fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionFilter {
+    pub major: u64,
+    pub formats: Vec<ArchiveFormat>,
+    pub include_prereleases: bool,
+    pub minors: Range<u64>,
+    /// Field separator of the index. Some mirrors serve it comma-separated.
+    pub delimiter: u8,
+    /// Lowest acceptable patch level per minor. Minors without an entry
+    /// accept any patch level.
+    pub min_patch: HashMap<u64, u64>,
+    /// Releases that are never valid, e.g. ones that were yanked.
+    pub denylist: Vec<SemVerVersion>,
+    /// When set, only these releases are valid.
+    pub allowlist: Option<Vec<SemVerVersion>>,
+    /// Reads names without a patch level, such as `ruby-3.2` in some old
+    /// snapshots, as `.0` releases instead of rejecting them.
+    pub lenient_patch: bool,
+}
+
+impl Default for VersionFilter {
+    fn default() -> Self {
+        Self {
+            major: DEFAULT_MAJOR,
+            formats: vec![ArchiveFormat::TarGz],
+            include_prereleases: false,
+            minors: MINOR_RANGE,
+            delimiter: b'\t',
+            min_patch: HashMap::new(),
+            denylist: vec![],
+            allowlist: None,
+            lenient_patch: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    TarGz,
+    TarXz,
+    TarBz2,
+    Zip,
+}
+
+impl ArchiveFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ArchiveFormat::TarGz => "tar.gz",
+            ArchiveFormat::TarXz => "tar.xz",
+            ArchiveFormat::TarBz2 => "tar.bz2",
+            ArchiveFormat::Zip => "zip",
+        }
+    }
 }
 
 impl Ord for Release {
@@ -47,56 +175,837 @@ impl PartialEq for Release {
     }
 }
 
+impl fmt::Display for Release {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ruby-{} ({})", self.version, self.url)
+    }
+}
+
 impl Release {
-    fn valid(&self) -> bool {
-        is_regular_release(&self.version) && has_tar_gz_url(&self.url)
+    pub fn new(version: SemVerVersion, url: String, sha256: String) -> Self {
+        Self {
+            version,
+            url,
+            sha256,
+            sha512: None,
+            released_on: None,
+        }
+    }
+
+    pub fn builder() -> ReleaseBuilder {
+        ReleaseBuilder::default()
+    }
+
+    pub fn version(&self) -> &SemVerVersion {
+        &self.version
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn sha256(&self) -> &str {
+        &self.sha256
+    }
+
+    pub fn sha512(&self) -> Option<&str> {
+        self.sha512.as_deref()
+    }
+
+    pub fn with_released_on(mut self, date: NaiveDate) -> Self {
+        self.released_on = Some(date);
+        self
+    }
+
+    pub fn released_on(&self) -> Option<NaiveDate> {
+        self.released_on
+    }
+
+    /// The series directory the tarball lives in, e.g. `3.2`. Falls back to
+    /// `major.minor` of the version when the URL has no such directory.
+    pub fn series_path(&self) -> String {
+        self.url
+            .rsplit('/')
+            .nth(1)
+            .filter(|dir| {
+                dir.split_once('.').is_some_and(|(major, minor)| {
+                    major.parse::<u64>().is_ok() && minor.parse::<u64>().is_ok()
+                })
+            })
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("{}.{}", self.version.major, self.version.minor))
+    }
+
+    /// The release's announcement on ruby-lang.org. Its URL contains the
+    /// release date, which the index doesn't carry, so without
+    /// [`Release::released_on`] this falls back to the news index.
+    pub fn news_url(&self) -> String {
+        let Some(date) = self.released_on else {
+            return NEWS_URL.to_string();
+        };
+        let mut slug = format!(
+            "ruby-{}-{}-{}",
+            self.version.major, self.version.minor, self.version.patch
+        );
+        if !self.version.pre.is_empty() {
+            slug.push('-');
+            slug.push_str(&self.version.pre.replace('.', "-"));
+        }
+        format!(
+            "{NEWS_URL}{}/{:02}/{:02}/{slug}-released/",
+            date.year(),
+            date.month(),
+            date.day()
+        )
+    }
+
+    /// The version as version managers such as rbenv expect it, e.g.
+    /// `3.2.2` or `3.3.0-preview1`, without any build metadata.
+    pub fn install_name(&self) -> String {
+        let mut version = self.version.clone();
+        version.build = BuildMetadata::EMPTY;
+        version.to_string()
+    }
+
+    /// The detached GPG signature published next to the tarball.
+    pub fn signature_url(&self) -> String {
+        format!("{}.asc", self.url)
+    }
+
+    pub fn valid(&self, filter: &VersionFilter) -> bool {
+        self.reason(filter).is_none()
+    }
+
+    /// Why the release isn't [`valid`](Release::valid) under `filter`, or
+    /// `None` if it is. Checks run in the same order as in `valid`, so only
+    /// the first failure is reported.
+    pub fn reason(&self, filter: &VersionFilter) -> Option<InvalidReason> {
+        if let Err(reason) = check_version(&self.version, filter) {
+            Some(reason)
+        } else if !has_accepted_archive(&self.url, &filter.formats) {
+            Some(InvalidReason::BadUrl)
+        } else if !self.has_valid_sha256() {
+            Some(InvalidReason::BadChecksum)
+        } else if !self.has_matching_url(filter) {
+            Some(InvalidReason::UrlMismatch)
+        } else {
+            None
+        }
+    }
+
+    /// Checks that the tarball file name carries the same version as the
+    /// name column. Patchless file names only match under
+    /// [`VersionFilter::lenient_patch`].
+    pub fn has_matching_url(&self, filter: &VersionFilter) -> bool {
+        url_version(&self.url, filter.lenient_patch).is_some_and(|v| v == self.version)
+    }
+
+    /// Unlike `==`, which only compares versions, also requires the same
+    /// URL and digest. Useful to spot mirrors disagreeing about a release.
+    pub fn strict_eq(&self, other: &Release) -> bool {
+        self.version == other.version && self.url == other.url && self.sha256 == other.sha256
+    }
+
+    /// Whether no valid release in `all` from the same series is newer.
+    pub fn is_latest_in_minor(&self, all: &[Release]) -> bool {
+        let filter = VersionFilter::default();
+        !all.iter().any(|r| {
+            r.version.major == self.version.major
+                && r.version.minor == self.version.minor
+                && r.valid(&filter)
+                && r > self
+        })
+    }
+
+    /// Security fixes ship as patch releases, so a regular release that bumps
+    /// the patch level of the previously known latest in the same series is
+    /// worth flagging. The index itself can't tell a security fix apart.
+    pub fn is_security_candidate(&self, previous_latest: &SemVerVersion) -> bool {
+        self.version.major == previous_latest.major
+            && self.version.minor == previous_latest.minor
+            && self.version.patch > previous_latest.patch
+            && self.version.pre.is_empty()
+    }
+
+    pub fn sha256_bytes(&self) -> Result<[u8; 32], CheckerError> {
+        let mut bytes = [0u8; 32];
+        hex::decode_to_slice(&self.sha256, &mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Checks downloaded `bytes` against the release's digest of the given
+    /// kind.
+    pub fn verify(&self, bytes: &[u8], algorithm: DigestAlgorithm) -> Result<(), CheckerError> {
+        match algorithm {
+            DigestAlgorithm::Sha256 => self.verify_sha256(bytes),
+            DigestAlgorithm::Sha512 => self.verify_sha512(bytes),
+        }
+    }
+
+    pub fn verify_sha256(&self, bytes: &[u8]) -> Result<(), CheckerError> {
+        compare_digest(&self.sha256, format!("{:x}", Sha256::digest(bytes)))
+    }
+
+    /// Fails with [`CheckerError::MissingChecksum`] for releases read from
+    /// an index without a `sha512` column.
+    pub fn verify_sha512(&self, bytes: &[u8]) -> Result<(), CheckerError> {
+        let expected = self
+            .sha512
+            .as_deref()
+            .ok_or(CheckerError::MissingChecksum(DigestAlgorithm::Sha512))?;
+        compare_digest(expected, format!("{:x}", Sha512::digest(bytes)))
+    }
+
+    /// The index publishes lowercase digests, so uppercase hex is rejected.
+    pub fn has_valid_sha256(&self) -> bool {
+        cached(&SHA256, SHA256_PATTERN).is_match(&self.sha256)
     }
 }
 
-fn is_regular_release(r: &SemVerVersion) -> bool {
-    r.major == 3
-        && MINOR_RANGE.contains(&r.minor)
-        && PATCH_RANGE.contains(&r.patch)
-        && r.pre.is_empty()
+fn compare_digest(expected: &str, actual: String) -> Result<(), CheckerError> {
+    if actual != expected {
+        return Err(CheckerError::ChecksumMismatch {
+            expected: expected.to_string(),
+            actual,
+        });
+    }
+    Ok(())
 }
 
-fn has_tar_gz_url(u: &str) -> bool {
-    let regex = Regex::new(r"https://.*\.tar\.gz$").unwrap();
-    regex.is_match(u)
+/// The digests the index publishes for each release.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DigestAlgorithm {
+    #[default]
+    Sha256,
+    Sha512,
 }
 
-pub async fn parse_data(csv: &str) -> Result<Vec<Release>, Box<dyn Error>> {
-    let mut result = vec![];
-    let mut csv = ReaderBuilder::new()
-        .delimiter(b'\t')
-        .from_reader(csv.as_bytes());
+impl fmt::Display for DigestAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DigestAlgorithm::Sha256 => write!(f, "sha256"),
+            DigestAlgorithm::Sha512 => write!(f, "sha512"),
+        }
+    }
+}
 
-    for line in csv.deserialize() {
-        let item: Release = match line {
-            Ok(release) => release,
-            Err(_) => continue,
-        };
-        if item.valid() {
-            result.push(item)
+/// Fluent constructor for [`Release`], mostly useful in tests. Unset fields
+/// default to version `0.0.0`, the tarball URL of that version on
+/// cache.ruby-lang.org and [`PLACEHOLDER_SHA256`], so a release built with
+/// just a valid version passes [`Release::valid`].
+#[derive(Debug, Clone, Default)]
+pub struct ReleaseBuilder {
+    version: Option<SemVerVersion>,
+    url: Option<String>,
+    sha256: Option<String>,
+    sha512: Option<String>,
+}
+
+impl ReleaseBuilder {
+    pub fn version(mut self, version: SemVerVersion) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    pub fn sha256(mut self, sha256: impl Into<String>) -> Self {
+        self.sha256 = Some(sha256.into());
+        self
+    }
+
+    pub fn sha512(mut self, sha512: impl Into<String>) -> Self {
+        self.sha512 = Some(sha512.into());
+        self
+    }
+
+    pub fn build(self) -> Release {
+        let version = self.version.unwrap_or(SemVerVersion::new(0, 0, 0));
+        let url = self.url.unwrap_or_else(|| {
+            format!(
+                "https://cache.ruby-lang.org/pub/ruby/{}.{}/ruby-{version}.tar.gz",
+                version.major, version.minor
+            )
+        });
+        let sha256 = self
+            .sha256
+            .unwrap_or_else(|| PLACEHOLDER_SHA256.to_string());
+        let mut release = Release::new(version, url, sha256);
+        release.sha512 = self.sha512;
+        release
+    }
+}
+
+/// What made [`Release::valid`] reject a release.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidReason {
+    WrongMajor,
+    MinorOutOfRange,
+    PatchOutOfRange,
+    HasPrerelease,
+    /// Build metadata (e.g. `3.2.2+1` for a rebuilt archive) is never
+    /// regular: it names the same release as the plain version, and keeping
+    /// both would report one Ruby version twice.
+    HasBuildMetadata,
+    BelowMinPatch,
+    Denied,
+    NotAllowed,
+    BadUrl,
+    BadChecksum,
+    UrlMismatch,
+}
+
+/// Whether `r` passes every version check of `filter`: the major, minor and
+/// patch ranges, pre-releases, build metadata, minimum patches and the deny-
+/// and allowlists. See [`InvalidReason`] for why a version fails.
+pub fn is_regular_release(r: &SemVerVersion, filter: &VersionFilter) -> bool {
+    check_version(r, filter).is_ok()
+}
+
+fn check_version(r: &SemVerVersion, filter: &VersionFilter) -> Result<(), InvalidReason> {
+    if r.major != filter.major {
+        Err(InvalidReason::WrongMajor)
+    } else if !filter.minors.contains(&r.minor) {
+        Err(InvalidReason::MinorOutOfRange)
+    } else if !PATCH_RANGE.contains(&r.patch) {
+        Err(InvalidReason::PatchOutOfRange)
+    } else if !filter.include_prereleases && !r.pre.is_empty() {
+        Err(InvalidReason::HasPrerelease)
+    } else if !r.build.is_empty() {
+        Err(InvalidReason::HasBuildMetadata)
+    } else if filter
+        .min_patch
+        .get(&r.minor)
+        .is_some_and(|floor| r.patch < *floor)
+    {
+        Err(InvalidReason::BelowMinPatch)
+    } else if filter.denylist.contains(r) {
+        Err(InvalidReason::Denied)
+    } else if filter
+        .allowlist
+        .as_ref()
+        .is_some_and(|allowed| !allowed.contains(r))
+    {
+        Err(InvalidReason::NotAllowed)
+    } else {
+        Ok(())
+    }
+}
+
+pub fn has_accepted_archive(u: &str, formats: &[ArchiveFormat]) -> bool {
+    cached(&ARCHIVE_URL, ARCHIVE_URL_PATTERN)
+        .captures(u)
+        .and_then(|c| c.get(1))
+        .is_some_and(|ext| formats.iter().any(|f| f.extension() == ext.as_str()))
+}
+
+/// With `lenient_patch`, a tarball named `ruby-3.2.tar.gz` is read as
+/// `3.2.0`. Only releases that already passed the name check are compared
+/// against it.
+fn url_version(u: &str, lenient_patch: bool) -> Option<SemVerVersion> {
+    let version = cached(&URL_VERSION, URL_VERSION_PATTERN)
+        .captures(u)?
+        .get(1)?
+        .as_str();
+    match lenient_patch {
+        true => normalize_patchless(version).parse().ok(),
+        false => version.parse().ok(),
+    }
+}
+
+/// Appends a `.0` patch level to `major.minor` versions, with or without the
+/// `ruby-` prefix. Anything else is returned unchanged.
+fn normalize_patchless(name: &str) -> Cow<'_, str> {
+    let version = name.strip_prefix("ruby-").unwrap_or(name);
+    let numeric = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    match version.split_once('.') {
+        Some((major, minor)) if numeric(major) && numeric(minor) => Cow::Owned(format!("{name}.0")),
+        _ => Cow::Borrowed(name),
+    }
+}
+
+pub fn parse_data(csv: &str) -> Result<Vec<Release>, CheckerError> {
+    parse_data_with_filter(csv, &VersionFilter::default())
+}
+
+pub fn parse_data_with_filter(
+    csv: &str,
+    filter: &VersionFilter,
+) -> Result<Vec<Release>, CheckerError> {
+    parse_report(csv, filter).map(|report| report.releases)
+}
+
+pub fn parse_file(path: &Path) -> Result<Vec<Release>, CheckerError> {
+    parse_file_with_filter(path, &VersionFilter::default())
+}
+
+/// Reads a local copy of the index, for hosts that can't reach a mirror.
+pub fn parse_file_with_filter(
+    path: &Path,
+    filter: &VersionFilter,
+) -> Result<Vec<Release>, CheckerError> {
+    parse_data_with_filter(&fs::read_to_string(path)?, filter)
+}
+
+/// Reads the whole index from `reader`, e.g. stdin in a pipeline. An empty
+/// input has no releases.
+pub fn parse_reader_with_filter<R: Read>(
+    mut reader: R,
+    filter: &VersionFilter,
+) -> Result<Vec<Release>, CheckerError> {
+    let mut data = String::new();
+    reader.read_to_string(&mut data)?;
+    parse_data_with_filter(&data, filter)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    BadPrefix,
+    BadSemVer,
+    Malformed,
+    /// The row parsed, but the release isn't [`valid`](Release::valid).
+    Invalid(InvalidReason),
+    Duplicate,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedRow {
+    pub line: String,
+    pub reason: SkipReason,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ParseReport {
+    pub releases: Vec<Release>,
+    pub skipped: Vec<SkippedRow>,
+}
+
+/// How many data rows the index had, and how many of them were kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseStats {
+    pub total: usize,
+    pub kept: usize,
+}
+
+impl fmt::Display for ParseStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "parsed {} rows, kept {}", self.total, self.kept)
+    }
+}
+
+impl ParseReport {
+    pub fn stats(&self) -> ParseStats {
+        ParseStats {
+            total: self.releases.len() + self.skipped.len(),
+            kept: self.releases.len(),
+        }
+    }
+}
+
+/// Parses the index like [`parse_data_with_filter`], but also reports every
+/// row that was dropped and why. Duplicate versions keep their first row.
+pub fn parse_report(csv: &str, filter: &VersionFilter) -> Result<ParseReport, CheckerError> {
+    let mut csv = reader(csv.as_bytes(), filter.delimiter);
+    let headers = csv.headers()?.clone();
+    check_headers(&headers)?;
+
+    let mut report = ParseReport::default();
+    let mut seen = HashSet::new();
+    for record in csv.byte_records() {
+        let record = record?;
+        let line = record
+            .iter()
+            .map(String::from_utf8_lossy)
+            .collect::<Vec<_>>()
+            .join(&char::from(filter.delimiter).to_string());
+        let release = StringRecord::from_byte_record(record)
+            .map_err(|_| SkipReason::Malformed)
+            .and_then(|record| parse_record(&record, &headers, filter));
+
+        match release {
+            Ok(release) if seen.insert(release.version.clone()) => report.releases.push(release),
+            Ok(_) => report.skipped.push(SkippedRow {
+                line,
+                reason: SkipReason::Duplicate,
+            }),
+            Err(reason) => report.skipped.push(SkippedRow { line, reason }),
         }
     }
-    Ok(result)
+    report.releases.sort();
+
+    #[cfg(feature = "logging")]
+    tracing::info!(
+        kept = report.releases.len(),
+        skipped = report.skipped.len(),
+        "Parsed release index"
+    );
+    Ok(report)
+}
+
+/// Parses the index like [`parse_data_with_filter`], but validates the rows
+/// on all cores once they've been read. Worth it for indexes with thousands
+/// of rows; the result is the same as the serial one.
+#[cfg(feature = "parallel")]
+pub fn parse_data_parallel(
+    csv: &str,
+    filter: &VersionFilter,
+) -> Result<Vec<Release>, CheckerError> {
+    let mut csv = reader(csv.as_bytes(), filter.delimiter);
+    let headers = csv.headers()?.clone();
+    check_headers(&headers)?;
+
+    let records = csv.byte_records().collect::<Result<Vec<_>, csv::Error>>()?;
+    let parsed: Vec<Option<Release>> = records
+        .into_par_iter()
+        .map(|record| {
+            StringRecord::from_byte_record(record)
+                .ok()
+                .and_then(|record| parse_record(&record, &headers, filter).ok())
+        })
+        .collect();
+
+    let mut seen = HashSet::new();
+    let mut releases: Vec<Release> = parsed
+        .into_iter()
+        .flatten()
+        .filter(|release| seen.insert(release.version.clone()))
+        .collect();
+    releases.sort();
+    Ok(releases)
+}
+
+/// Sorts releases and drops repeated versions, keeping the first occurrence.
+pub fn deduplicated(versions: Vec<Release>) -> Vec<Release> {
+    let mut versions = versions;
+    versions.sort();
+    versions.dedup();
+    versions
+}
+
+/// Combines releases from two shards of the index. A version present in
+/// both keeps the release from `a`.
+pub fn merge(a: Vec<Release>, b: Vec<Release>) -> Vec<Release> {
+    let mut versions = a;
+    versions.extend(b);
+    deduplicated(versions)
+}
+
+pub fn parse_data_iter<R: Read>(reader: R) -> impl Iterator<Item = Result<Release, CheckerError>> {
+    parse_data_iter_with_filter(reader, VersionFilter::default())
+}
+
+/// Lazily yields the valid releases of an index. Rows that don't parse or
+/// don't pass the filter are skipped; only read errors are yielded. Unlike
+/// [`parse_data`], duplicate versions are passed through as they appear.
+pub fn parse_data_iter_with_filter<R: Read>(
+    reader: R,
+    filter: VersionFilter,
+) -> impl Iterator<Item = Result<Release, CheckerError>> {
+    let mut csv = self::reader(reader, filter.delimiter);
+
+    let (headers, error) = match csv.headers() {
+        Ok(headers) => match check_headers(headers) {
+            Ok(()) => (headers.clone(), None),
+            Err(err) => (StringRecord::new(), Some(Err(err))),
+        },
+        Err(err) => (StringRecord::new(), Some(Err(err.into()))),
+    };
+    let records = error.is_none().then(|| csv.into_records());
+
+    error.into_iter().chain(
+        records
+            .into_iter()
+            .flatten()
+            .filter_map(move |record| match record {
+                Ok(record) => parse_record(&record, &headers, &filter).ok().map(Ok),
+                Err(err) if err.is_io_error() => Some(Err(err.into())),
+                Err(_) => None,
+            }),
+    )
+}
+
+/// Fields are trimmed, since a stray `\r` or space from another platform's
+/// line endings would otherwise fail the URL and digest checks.
+fn reader<R: Read>(reader: R, delimiter: u8) -> csv::Reader<R> {
+    ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .trim(Trim::All)
+        .from_reader(reader)
+}
+
+const REQUIRED_COLUMNS: [&str; 3] = ["name", "url", "sha256"];
+
+/// Fields are bound by header name, so columns may come in any order and
+/// optional ones (`sha1`, `sha512`) may be missing. An empty input has no
+/// header at all and is let through as an empty index.
+fn check_headers(headers: &StringRecord) -> Result<(), CheckerError> {
+    if headers.is_empty() {
+        return Ok(());
+    }
+    match REQUIRED_COLUMNS
+        .iter()
+        .find(|column| !headers.iter().any(|h| h == **column))
+    {
+        Some(column) => Err(CheckerError::MissingColumn(column.to_string())),
+        None => Ok(()),
+    }
+}
+
+fn parse_record(
+    record: &StringRecord,
+    headers: &StringRecord,
+    filter: &VersionFilter,
+) -> Result<Release, SkipReason> {
+    let name_index = headers.iter().position(|h| h == "name");
+    let result = check_record(record, headers, name_index, filter);
+
+    #[cfg(feature = "logging")]
+    if let Err(reason) = &result {
+        let name = name_index.and_then(|index| record.get(index));
+        tracing::debug!(name, ?reason, "Skipping invalid release");
+    }
+    result
+}
+
+fn check_record(
+    record: &StringRecord,
+    headers: &StringRecord,
+    name_index: Option<usize>,
+    filter: &VersionFilter,
+) -> Result<Release, SkipReason> {
+    let index = name_index.ok_or(SkipReason::Malformed)?;
+    let name = record.get(index).ok_or(SkipReason::Malformed)?;
+    let name = match filter.lenient_patch {
+        true => normalize_patchless(name),
+        false => Cow::Borrowed(name),
+    };
+    match parse_version(&name) {
+        Err(CheckerError::MissingPrefix) => return Err(SkipReason::BadPrefix),
+        Err(_) => return Err(SkipReason::BadSemVer),
+        Ok(_) => {}
+    }
+
+    let release: Release = match name {
+        Cow::Borrowed(_) => record.deserialize(Some(headers)),
+        Cow::Owned(name) => record
+            .iter()
+            .enumerate()
+            .map(|(i, field)| if i == index { name.as_str() } else { field })
+            .collect::<StringRecord>()
+            .deserialize(Some(headers)),
+    }
+    .map_err(|_| SkipReason::Malformed)?;
+    match release.reason(filter) {
+        Some(reason) => Err(SkipReason::Invalid(reason)),
+        None => Ok(release),
+    }
+}
+
+pub fn latest_versions(versions: Vec<Release>) -> Vec<Release> {
+    latest_versions_with_filter(versions, &VersionFilter::default())
 }
 
-pub async fn latest_versions(versions: Vec<Release>) -> Vec<Release> {
-    let mut releases: Vec<Release> = vec![];
-    for number in MINOR_RANGE {
-        let mut v = versions.clone();
-        v.retain(|r| r.version.minor == number);
-        v.sort();
-        if let Some(r) = v.last() {
-            releases.push(r.to_owned())
+pub fn latest_versions_with_filter(versions: Vec<Release>, filter: &VersionFilter) -> Vec<Release> {
+    let mut latest: HashMap<u64, Release> = HashMap::new();
+    for release in versions {
+        if !filter.minors.contains(&release.version.minor) {
+            continue;
+        }
+        match latest.entry(release.version.minor) {
+            Entry::Occupied(mut current) => {
+                if release >= *current.get() {
+                    current.insert(release);
+                }
+            }
+            Entry::Vacant(slot) => {
+                slot.insert(release);
+            }
         }
     }
 
+    let mut releases: Vec<Release> = latest.into_values().collect();
+    releases.sort_by_key(|r| r.version.minor);
     releases
 }
 
+pub fn latest_versions_for_major(versions: Vec<Release>, major: u64) -> Vec<Release> {
+    let filter = VersionFilter {
+        major,
+        ..Default::default()
+    };
+    let mut versions = versions;
+    versions.retain(|r| r.valid(&filter));
+    latest_versions_with_filter(versions, &filter)
+}
+
+/// The latest valid release of each of the `n` highest minor series, oldest
+/// series first. Returns every series if there are fewer than `n`.
+pub fn latest_n_minors(versions: Vec<Release>, n: usize) -> Vec<Release> {
+    let filter = VersionFilter::default();
+    let mut versions = versions;
+    versions.retain(|r| r.valid(&filter));
+    let mut latest = latest_versions_with_filter(versions, &filter);
+    latest.drain(..latest.len().saturating_sub(n));
+    latest
+}
+
+pub fn all_valid_sorted(versions: Vec<Release>) -> Vec<Release> {
+    let filter = VersionFilter::default();
+    let mut versions = versions;
+    versions.retain(|r| r.valid(&filter));
+    versions.sort();
+    versions
+}
+
+pub fn sorted_descending(versions: Vec<Release>) -> Vec<Release> {
+    let mut versions = all_valid_sorted(versions);
+    versions.reverse();
+    versions
+}
+
+pub fn latest_for_minor(versions: Vec<Release>, minor: u64) -> Option<Release> {
+    let filter = VersionFilter::default();
+    versions
+        .into_iter()
+        .filter(|r| r.version.minor == minor && r.valid(&filter))
+        .max()
+}
+
+pub fn newest(versions: Vec<Release>) -> Option<Release> {
+    let filter = VersionFilter::default();
+    versions.into_iter().filter(|r| r.valid(&filter)).max()
+}
+
+pub fn group_by_minor(versions: Vec<Release>) -> BTreeMap<u64, Vec<Release>> {
+    let filter = VersionFilter::default();
+    let mut groups: BTreeMap<u64, Vec<Release>> = BTreeMap::new();
+    for release in versions.into_iter().filter(|r| r.valid(&filter)) {
+        groups
+            .entry(release.version.minor)
+            .or_default()
+            .push(release);
+    }
+    for group in groups.values_mut() {
+        group.sort();
+    }
+    groups
+}
+
+/// The distinct minor series among the valid releases, lowest first.
+pub fn available_minors(versions: &[Release]) -> Vec<u64> {
+    let filter = VersionFilter::default();
+    let minors: BTreeSet<u64> = versions
+        .iter()
+        .filter(|r| r.valid(&filter))
+        .map(|r| r.version.minor)
+        .collect();
+    minors.into_iter().collect()
+}
+
+pub fn versions_at_least(versions: Vec<Release>, floor: SemVerVersion) -> Vec<Release> {
+    versions_at_least_with_filter(versions, floor, &VersionFilter::default())
+}
+
+pub fn versions_at_least_with_filter(
+    versions: Vec<Release>,
+    floor: SemVerVersion,
+    filter: &VersionFilter,
+) -> Vec<Release> {
+    let mut versions = versions;
+    versions.retain(|r| r.version >= floor);
+    latest_versions_with_filter(versions, filter)
+}
+
+/// Valid releases satisfying `req`, e.g. `>=3.1, <3.3`. Chain with
+/// [`latest_versions`] to get one release per series.
+pub fn matching(versions: Vec<Release>, req: &VersionReq) -> Vec<Release> {
+    let filter = VersionFilter::default();
+    let mut versions = versions;
+    versions.retain(|r| r.valid(&filter) && req.matches(&r.version));
+    versions
+}
+
+/// The newest valid release matching a wildcard such as `3.2.x` or `3.x`.
+/// Returns `None` for anything else, including patterns without a wildcard.
+pub fn resolve_wildcard(versions: Vec<Release>, pattern: &str) -> Option<Release> {
+    let prefix = pattern.strip_suffix(".x")?;
+    let parts: Vec<&str> = prefix.split('.').collect();
+    if parts.len() > 2 || !parts.iter().all(|p| p.parse::<u64>().is_ok()) {
+        return None;
+    }
+    let req = VersionReq::parse(pattern).ok()?;
+    matching(versions, &req).into_iter().max()
+}
+
+/// Valid releases strictly newer than `current`, oldest first.
+pub fn newer_than(versions: Vec<Release>, current: &SemVerVersion) -> Vec<Release> {
+    let mut versions = all_valid_sorted(versions);
+    versions.retain(|r| r.version > *current);
+    versions
+}
+
+/// Just the version numbers of `releases`, in the same order.
+pub fn versions_only(releases: &[Release]) -> Vec<SemVerVersion> {
+    releases.iter().map(|r| r.version.clone()).collect()
+}
+
+/// `(minor, patch)` of each release, typically the output of
+/// [`latest_versions`], so callers can compare how far each series got.
+pub fn patch_spread(latest: &[Release]) -> Vec<(u64, u64)> {
+    latest
+        .iter()
+        .map(|r| (r.version.minor, r.version.patch))
+        .collect()
+}
+
+/// Whether exactly `version` is among `versions`, e.g. to notice a pinned
+/// release being pruned from a mirror.
+pub fn contains_version(versions: &[Release], version: &SemVerVersion) -> bool {
+    versions.iter().any(|r| r.version == *version)
+}
+
+pub fn is_outdated(installed: &SemVerVersion, latest: &[Release]) -> Option<Release> {
+    latest
+        .iter()
+        .filter(|r| r.version.major == installed.major && r.version.minor == installed.minor)
+        .max()
+        .filter(|r| r.version > *installed)
+        .cloned()
+}
+
+/// A consistency problem in an already parsed list of releases.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DatasetIssue {
+    Duplicate(SemVerVersion),
+    Invalid(SemVerVersion),
+    OutOfOrder(SemVerVersion),
+}
+
+/// Checks that a parsed list, such as the output of [`parse_data`], is
+/// sorted, free of duplicates and only holds valid releases. An empty
+/// result means the list is consistent.
+pub fn validate_dataset(versions: &[Release]) -> Vec<DatasetIssue> {
+    let filter = VersionFilter::default();
+    let mut issues = vec![];
+    let mut seen = HashSet::new();
+    for (i, release) in versions.iter().enumerate() {
+        if !seen.insert(&release.version) {
+            issues.push(DatasetIssue::Duplicate(release.version.clone()));
+        }
+        if !release.valid(&filter) {
+            issues.push(DatasetIssue::Invalid(release.version.clone()));
+        }
+        if i > 0 && release.version < versions[i - 1].version {
+            issues.push(DatasetIssue::OutOfOrder(release.version.clone()));
+        }
+    }
+    issues
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -106,33 +1015,527 @@ mod test {
 
     struct Data {
         version: &'static str,
-        url: &'static str,
+        url: String,
     }
 
     #[test]
     fn validates_good_version() {
+        let filter = VersionFilter::default();
         for version in convert_to_versions(good_data()) {
-            assert!(version.valid())
+            assert!(version.valid(&filter))
+        }
+
+        for version in convert_to_versions(good_and_bad_data_with_bad_urls()) {
+            assert!(!version.valid(&filter))
         }
+    }
+
+    #[test]
+    fn explains_why_releases_are_invalid() {
+        let filter = VersionFilter {
+            min_patch: HashMap::from([(1, 4)]),
+            denylist: vec!["3.2.1".parse().unwrap()],
+            ..Default::default()
+        };
+        let reason = |version: &str, url: Option<&str>, sha256: &str| {
+            let mut builder = Release::builder()
+                .version(version.parse().unwrap())
+                .sha256(sha256);
+            if let Some(url) = url {
+                builder = builder.url(url);
+            }
+            builder.build().reason(&filter)
+        };
+        let sha = good_sha256();
+
+        assert_eq!(reason("3.2.2", None, sha), None);
+        assert_eq!(reason("2.7.8", None, sha), Some(InvalidReason::WrongMajor));
+        assert_eq!(
+            reason("3.150.0", None, sha),
+            Some(InvalidReason::MinorOutOfRange)
+        );
+        assert_eq!(
+            reason("3.2.150", None, sha),
+            Some(InvalidReason::PatchOutOfRange)
+        );
+        assert_eq!(
+            reason("3.3.0-preview1", None, sha),
+            Some(InvalidReason::HasPrerelease)
+        );
+        assert_eq!(
+            reason("3.2.2+1", None, sha),
+            Some(InvalidReason::HasBuildMetadata)
+        );
+        assert_eq!(
+            reason("3.1.3", None, sha),
+            Some(InvalidReason::BelowMinPatch)
+        );
+        assert_eq!(reason("3.2.1", None, sha), Some(InvalidReason::Denied));
+        assert_eq!(
+            reason("3.2.2", Some("http://example.com/ruby-3.2.2.tar.gz"), sha),
+            Some(InvalidReason::BadUrl)
+        );
+        assert_eq!(reason("3.2.2", None, "-"), Some(InvalidReason::BadChecksum));
+        assert_eq!(
+            reason("3.2.2", Some(&tarball_url("3.2.3")), sha),
+            Some(InvalidReason::UrlMismatch)
+        );
+
+        let allowlisted = VersionFilter {
+            allowlist: Some(vec!["3.3.0".parse().unwrap()]),
+            ..Default::default()
+        };
+        let release = Release::builder()
+            .version("3.2.2".parse().unwrap())
+            .sha256(sha)
+            .build();
+        assert_eq!(
+            release.reason(&allowlisted),
+            Some(InvalidReason::NotAllowed)
+        );
+    }
+
+    #[test]
+    fn validates_against_configured_major() {
+        let version = "3.2.0".parse::<SemVerVersion>().unwrap();
+
+        let three = VersionFilter::default();
+        let two = VersionFilter {
+            major: 2,
+            ..Default::default()
+        };
+
+        assert!(is_regular_release(&version, &three));
+        assert!(!is_regular_release(&version, &two));
+    }
+
+    #[test]
+    fn rejects_patches_below_minor_floor() {
+        let filter = VersionFilter {
+            min_patch: HashMap::from([(1, 4)]),
+            ..Default::default()
+        };
+        let release = |v: &str| Release::builder().version(v.parse().unwrap()).build();
+
+        assert!(!release("3.1.3").valid(&filter));
+        assert!(release("3.1.4").valid(&filter));
+        assert!(release("3.1.12").valid(&filter));
+        assert!(release("3.2.0").valid(&filter));
+        assert!(release("3.1.3").valid(&VersionFilter::default()));
+    }
+
+    #[test]
+    fn skips_denied_releases() {
+        let filter = VersionFilter {
+            denylist: vec!["3.2.11".parse().unwrap()],
+            ..Default::default()
+        };
+        let mut releases = convert_to_versions(good_data());
+        releases.retain(|r| r.valid(&filter));
+
+        let latest = latest_versions_with_filter(releases, &filter);
+        let versions: Vec<String> = latest.iter().map(|r| r.version.to_string()).collect();
+        assert_eq!(versions, vec!["3.1.12", "3.2.2", "3.3.12"]);
+
+        let filter = VersionFilter {
+            denylist: vec!["3.2.2".parse().unwrap()],
+            ..Default::default()
+        };
+        let mut releases = convert_to_versions(good_data());
+        assert!(!releases[4].valid(&filter));
+        releases.retain(|r| r.valid(&filter));
+        let latest = latest_versions_with_filter(releases, &filter);
+        assert_eq!(latest[1].version, "3.2.11".parse().unwrap());
+    }
+
+    #[test]
+    fn only_keeps_allowed_releases() {
+        let filter = VersionFilter {
+            allowlist: Some(vec!["3.2.2".parse().unwrap()]),
+            ..Default::default()
+        };
+        let mut releases = convert_to_versions(good_data());
+        releases.retain(|r| r.valid(&filter));
+
+        assert_eq!(releases.len(), 1);
+        assert_eq!(releases[0].version, "3.2.2".parse().unwrap());
+    }
+
+    #[test]
+    fn rejects_build_metadata() {
+        let filter = VersionFilter::default();
+        let plain = "3.2.2".parse::<SemVerVersion>().unwrap();
+        let rebuilt = "3.2.2+1".parse::<SemVerVersion>().unwrap();
+
+        assert!(is_regular_release(&plain, &filter));
+        assert!(!is_regular_release(&rebuilt, &filter));
+
+        let lines = "\
+name	url	sha1	sha256	sha512
+ruby-3.2.2+1	https://cache.ruby-lang.org/pub/ruby/3.2/ruby-3.2.2+1.tar.gz	670fce00d83771a1349b116e56a8a3b0ad323769	96c57558871a6748de5bc9f274e93f4b5aad06cd8f37befa0e8d94e7b8a423bc	bcc68f3f24c1c8987d9c80b57332e5791f25b935ba38daf5addf60dbfe3a05f9dcaf21909681b88e862c67c6ed103150f73259c6e35c564f13a00f432e3c1e46
+ruby-3.2.2	https://cache.ruby-lang.org/pub/ruby/3.2/ruby-3.2.2.tar.gz	670fce00d83771a1349b116e56a8a3b0ad323769	96c57558871a6748de5bc9f274e93f4b5aad06cd8f37befa0e8d94e7b8a423bc	bcc68f3f24c1c8987d9c80b57332e5791f25b935ba38daf5addf60dbfe3a05f9dcaf21909681b88e862c67c6ed103150f73259c6e35c564f13a00f432e3c1e46
+";
+        let report = parse_report(lines, &filter).unwrap();
+        assert_eq!(report.releases.len(), 1);
+        assert!(report.releases[0].version.build.is_empty());
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(
+            report.skipped[0].reason,
+            SkipReason::Invalid(InvalidReason::HasBuildMetadata)
+        );
+    }
+
+    #[test]
+    fn parses_other_majors_with_filter() {
+        let content = fs::read_to_string("test/fixtures/index.txt").unwrap();
+        let releases = parse_data_with_filter(
+            &content,
+            &VersionFilter {
+                major: 2,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        assert!(!releases.is_empty());
+        assert!(releases.iter().all(|r| r.version.major == 2));
+    }
+
+    #[test]
+    fn displays_name_and_url() {
+        let release = Release::new(
+            "3.2.2".parse().unwrap(),
+            "https://cache.ruby-lang.org/pub/ruby/3.2/ruby-3.2.2.tar.gz".to_string(),
+            good_sha256().to_string(),
+        );
+
+        assert_eq!(
+            release.to_string(),
+            "ruby-3.2.2 (https://cache.ruby-lang.org/pub/ruby/3.2/ruby-3.2.2.tar.gz)"
+        );
+    }
+
+    #[test]
+    fn builds_releases_with_defaults() {
+        let release = Release::builder().build();
+        assert_eq!(release.version, SemVerVersion::new(0, 0, 0));
+        assert_eq!(
+            release.url,
+            "https://cache.ruby-lang.org/pub/ruby/0.0/ruby-0.0.0.tar.gz"
+        );
+        assert_eq!(release.sha256, PLACEHOLDER_SHA256);
+        assert!(release.has_valid_sha256());
+
+        let release = Release::builder().version("3.2.2".parse().unwrap()).build();
+        assert_eq!(release.url, tarball_url("3.2.2"));
+        assert!(release.valid(&VersionFilter::default()));
+    }
+
+    #[test]
+    fn derives_series_path() {
+        let release = Release::builder().version("3.2.2".parse().unwrap()).build();
+        assert_eq!(release.series_path(), "3.2");
+
+        let release = Release::builder()
+            .version("3.2.2".parse().unwrap())
+            .url("https://mirror.internal/ruby-3.2.2.tar.gz")
+            .build();
+        assert_eq!(release.series_path(), "3.2");
+
+        let release = Release::builder()
+            .version("0.49.0".parse().unwrap())
+            .url("https://cache.ruby-lang.org/pub/ruby/1.0/ruby-0.49.tar.gz")
+            .build();
+        assert_eq!(release.series_path(), "1.0");
+    }
+
+    #[test]
+    fn derives_news_url() {
+        let release = |v: &str| Release::builder().version(v.parse().unwrap()).build();
+
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+        assert_eq!(release("3.2.2").news_url(), NEWS_URL);
+        assert_eq!(
+            release("3.2.2")
+                .with_released_on(date(2023, 3, 30))
+                .news_url(),
+            "https://www.ruby-lang.org/en/news/2023/03/30/ruby-3-2-2-released/"
+        );
+        assert_eq!(
+            release("3.3.0-preview1")
+                .with_released_on(date(2023, 5, 12))
+                .news_url(),
+            "https://www.ruby-lang.org/en/news/2023/05/12/ruby-3-3-0-preview1-released/"
+        );
+    }
+
+    #[test]
+    fn derives_install_name() {
+        let release = Release::builder()
+            .version(parse_version("ruby-3.2.2").unwrap())
+            .build();
+        assert_eq!(release.install_name(), "3.2.2");
+
+        let release = Release::builder()
+            .version("3.3.0-preview1+build.5".parse().unwrap())
+            .build();
+        assert_eq!(release.install_name(), "3.3.0-preview1");
+    }
+
+    #[test]
+    fn derives_signature_url() {
+        let release = Release::builder().version("3.2.2".parse().unwrap()).build();
+
+        assert_eq!(
+            release.signature_url(),
+            "https://cache.ruby-lang.org/pub/ruby/3.2/ruby-3.2.2.tar.gz.asc"
+        );
+    }
+
+    #[test]
+    fn rejects_names_without_prefix() {
+        assert!(matches!(
+            parse_version("jruby-9.4.0.0"),
+            Err(CheckerError::MissingPrefix)
+        ));
+        assert!(matches!(
+            parse_version("ruby-3.2"),
+            Err(CheckerError::SemVer(_))
+        ));
+        assert_eq!(
+            parse_version("ruby-3.2.2").unwrap(),
+            "3.2.2".parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn parses_ruby_versions_with_and_without_prefix() {
+        let expected = RubyVersion("3.2.2".parse().unwrap());
+
+        assert_eq!("3.2.2".parse::<RubyVersion>().unwrap(), expected);
+        assert_eq!("ruby-3.2.2".parse::<RubyVersion>().unwrap(), expected);
+        assert_eq!(expected.to_string(), "ruby-3.2.2");
+        assert!(matches!(
+            "2".parse::<RubyVersion>(),
+            Err(CheckerError::SemVer(_))
+        ));
+        assert!(matches!(
+            "ruby-2".parse::<RubyVersion>(),
+            Err(CheckerError::SemVer(_))
+        ));
+    }
+
+    #[test]
+    fn only_allows_lowercase_hex_digests() {
+        let mut release = convert_to_versions(good_data()).remove(0);
+        assert!(release.has_valid_sha256());
+
+        for sha256 in [&good_sha256()[1..], &good_sha256().to_uppercase(), "", "-"] {
+            release.sha256 = sha256.to_string();
+            assert!(!release.has_valid_sha256());
+            assert!(!release.valid(&VersionFilter::default()));
+        }
+    }
+
+    #[test]
+    fn rejects_urls_for_other_versions() {
+        let filter = VersionFilter::default();
+        let mut release = convert_to_versions(good_data()).remove(0);
+        assert!(release.has_matching_url(&filter));
+        assert!(release.valid(&filter));
+
+        release.url = "https://cache.ruby-lang.org/pub/ruby/3.1/ruby-3.1.0.tar.gz".to_string();
+        assert!(!release.has_matching_url(&filter));
+        assert!(!release.valid(&filter));
+    }
+
+    #[test]
+    fn matches_patchless_urls_only_when_lenient() {
+        let release = Release::builder()
+            .version("3.2.0".parse().unwrap())
+            .url("https://cache.ruby-lang.org/pub/ruby/3.2/ruby-3.2.tar.gz")
+            .build();
+        let lenient = VersionFilter {
+            lenient_patch: true,
+            ..Default::default()
+        };
+
+        assert!(!release.has_matching_url(&VersionFilter::default()));
+        assert_eq!(
+            release.reason(&VersionFilter::default()),
+            Some(InvalidReason::UrlMismatch)
+        );
+        assert!(release.has_matching_url(&lenient));
+        assert!(release.valid(&lenient));
+    }
+
+    #[test]
+    fn strict_equality_compares_url_and_digest() {
+        let release = convert_to_versions(good_data()).remove(0);
+        let mut mirrored = release.clone();
+        mirrored.url = "https://mirror.internal/ruby-3.3.0.tar.gz".to_string();
+
+        assert!(release.strict_eq(&release.clone()));
+        assert_eq!(release, mirrored);
+        assert!(!release.strict_eq(&mirrored));
+    }
+
+    #[test]
+    fn knows_latest_release_of_its_minor() {
+        let releases = convert_to_versions(good_data());
+        let find = |v: &str| {
+            releases
+                .iter()
+                .find(|r| r.version.to_string() == v)
+                .unwrap()
+        };
+
+        assert!(find("3.2.11").is_latest_in_minor(&releases));
+        assert!(find("3.1.12").is_latest_in_minor(&releases));
+        assert!(!find("3.2.0").is_latest_in_minor(&releases));
+        assert!(!find("3.2.2").is_latest_in_minor(&releases));
+    }
+
+    #[test]
+    fn flags_patch_bumps_as_security_candidates() {
+        let release = |v: &str| Release::builder().version(v.parse().unwrap()).build();
+        let previous = "3.2.2".parse().unwrap();
+
+        assert!(release("3.2.11").is_security_candidate(&previous));
+        assert!(!release("3.2.2").is_security_candidate(&previous));
+        assert!(!release("3.2.1").is_security_candidate(&previous));
+        assert!(!release("3.3.0").is_security_candidate(&previous));
+        assert!(!release("3.2.3-preview1").is_security_candidate(&previous));
+    }
+
+    #[test]
+    fn rejects_urls_without_a_version() {
+        let release = Release::builder()
+            .version("3.2.2".parse().unwrap())
+            .url("https://host/weird.tar.gz")
+            .build();
+
+        assert_eq!(url_version(release.url(), false), None);
+        assert!(!release.has_matching_url(&VersionFilter::default()));
+        assert!(!release.valid(&VersionFilter::default()));
+
+        for url in [
+            "https://host/ruby-.tar.gz",
+            "https://host/ruby-x.y.z.tar.gz",
+            "",
+        ] {
+            assert_eq!(url_version(url, true), None);
+        }
+    }
+
+    #[test]
+    fn reads_sha512_from_index() {
+        let fixture = fs::read_to_string("test/fixtures/index.txt").unwrap();
+        let release = parse_data(&fixture).unwrap().pop().unwrap();
+        assert_eq!(release.sha512().map(str::len), Some(128));
+
+        let data = "name\turl\tsha256\nruby-3.2.2\thttps://cache.ruby-lang.org/pub/ruby/3.2/ruby-3.2.2.tar.gz\t96c57558871a6748de5bc9f274e93f4b5aad06cd8f37befa0e8d94e7b8a423bc";
+        let release = parse_data(data).unwrap().pop().unwrap();
+        assert_eq!(release.sha512(), None);
+    }
+
+    #[test]
+    fn verifies_blob_against_both_digests() {
+        let release = Release::builder()
+            .sha256("bbdb87a8b8c1e21e697badb730f9fe4b99f5f15a86e29579dfb010da550dfcad")
+            .sha512("4a7cdc529b545378d3ddd3f45a9bf72c2de352c64624696d14551a626fc698a10b5b56b4397fed30e0b4fe61657a0bd6cb12cfd985118ed30fb0e4f2c93747c2")
+            .build();
+
+        for algorithm in [DigestAlgorithm::Sha256, DigestAlgorithm::Sha512] {
+            assert!(release.verify(b"ruby tarball", algorithm).is_ok());
+            assert!(matches!(
+                release.verify(b"tampered tarball", algorithm),
+                Err(CheckerError::ChecksumMismatch { .. })
+            ));
+        }
+
+        let release = Release::builder().build();
+        assert!(matches!(
+            release.verify_sha512(b"ruby tarball"),
+            Err(CheckerError::MissingChecksum(DigestAlgorithm::Sha512))
+        ));
+    }
+
+    #[test]
+    fn decodes_sha256_to_bytes() {
+        let mut release = convert_to_versions(good_data()).remove(0);
+        let bytes = release.sha256_bytes().unwrap();
+        assert_eq!(bytes[0], 0x96);
+        assert_eq!(bytes[31], 0xbc);
+
+        release.sha256 = good_sha256()[1..].to_string();
+        assert!(matches!(
+            release.sha256_bytes(),
+            Err(CheckerError::MalformedChecksum(
+                hex::FromHexError::OddLength
+            ))
+        ));
+    }
+
+    #[test]
+    fn only_allows_tar_gz_urls() {
+        let formats = VersionFilter::default().formats;
+        assert!(has_accepted_archive(good_url(), &formats));
+
+        for url in bad_urls() {
+            assert!(!has_accepted_archive(url, &formats))
+        }
+    }
+
+    #[test]
+    fn allows_requested_archive_formats() {
+        let url = "https://cache.ruby-lang.org/pub/ruby/3.0/ruby-3.0.2.tar.xz";
+        let default = VersionFilter::default();
+        let xz = VersionFilter {
+            formats: vec![ArchiveFormat::TarGz, ArchiveFormat::TarXz],
+            ..Default::default()
+        };
 
-        for version in convert_to_versions(good_and_bad_data_with_bad_urls()) {
-            assert!(!version.valid())
-        }
+        assert!(!has_accepted_archive(url, &default.formats));
+        assert!(has_accepted_archive(url, &xz.formats));
+        assert!(has_accepted_archive(good_url(), &xz.formats));
+        assert!(!has_accepted_archive(
+            "https://cache.ruby-lang.org/pub/ruby/3.0/ruby-3.0.2.zip",
+            &xz.formats
+        ));
     }
 
     #[test]
-    fn only_allows_tar_gz_urls() {
-        assert!(has_tar_gz_url(good_url()));
+    fn cached_patterns_match_like_fresh_ones() {
+        let content = fs::read_to_string("test/fixtures/index.txt").unwrap();
+        let archive = Regex::new(ARCHIVE_URL_PATTERN).unwrap();
+        let sha256 = Regex::new(SHA256_PATTERN).unwrap();
 
-        for url in bad_urls() {
-            assert!(!has_tar_gz_url(url))
+        for line in content.lines().skip(1) {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let (url, digest) = (fields[1], fields[3]);
+            for _ in 0..2 {
+                assert_eq!(
+                    cached(&ARCHIVE_URL, ARCHIVE_URL_PATTERN).is_match(url),
+                    archive.is_match(url)
+                );
+                assert_eq!(
+                    cached(&SHA256, SHA256_PATTERN).is_match(digest),
+                    sha256.is_match(digest)
+                );
+            }
         }
+        assert!(std::ptr::eq(
+            cached(&ARCHIVE_URL, ARCHIVE_URL_PATTERN),
+            cached(&ARCHIVE_URL, ARCHIVE_URL_PATTERN)
+        ));
     }
 
-    #[tokio::test]
-    async fn parse_correct_csv() {
+    #[test]
+    fn parse_correct_csv() {
         let content = fs::read_to_string("test/fixtures/index.txt").unwrap();
-        let releases = parse_data(&content).await.unwrap();
+        let releases = parse_data(&content).unwrap();
         let first: &Release = releases.first().unwrap();
         assert_eq!(
             first.version,
@@ -145,7 +1548,7 @@ mod test {
             }
         );
 
-        let latest = latest_versions(releases).await;
+        let latest = latest_versions(releases);
         assert_eq!(latest.len(), 3);
         assert_eq!(latest[0].version.minor, 1);
         assert_eq!(latest[1].version.minor, 2);
@@ -155,13 +1558,85 @@ mod test {
         assert_eq!(latest[2].version.patch, 0);
     }
 
-    #[tokio::test]
-    async fn parse_one_line_correctly() {
+    #[test]
+    fn parses_index_from_file() {
+        let releases = parse_file(Path::new("test/fixtures/index.txt")).unwrap();
+        assert_eq!(latest_versions(releases).len(), 3);
+
+        assert!(matches!(
+            parse_file(Path::new("test/fixtures/missing.txt")),
+            Err(CheckerError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound
+        ));
+    }
+
+    #[test]
+    fn parses_index_from_reader() {
+        let content = fs::read_to_string("test/fixtures/index.txt").unwrap();
+        let filter = VersionFilter::default();
+
+        let releases = parse_reader_with_filter(content.as_bytes(), &filter).unwrap();
+        assert_eq!(releases, parse_data(&content).unwrap());
+        assert!(parse_reader_with_filter(std::io::empty(), &filter)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn reports_patch_spread_of_latest_versions() {
+        let content = fs::read_to_string("test/fixtures/index.txt").unwrap();
+        let latest = latest_versions(parse_data(&content).unwrap());
+
+        assert_eq!(patch_spread(&latest), vec![(1, 4), (2, 2), (3, 0)]);
+    }
+
+    #[test]
+    fn binds_columns_by_header_name() {
+        let reordered = "\
+sha256	name	url
+fe6e4782de97443978ddba8ba4be38d222aa24dc3e3f02a6a8e7701c0eeb619d	ruby-3.1.1	https://cache.ruby-lang.org/pub/ruby/3.1/ruby-3.1.1.tar.gz
+";
+        let releases = parse_data(reordered).unwrap();
+        assert_eq!(releases.len(), 1);
+        assert_eq!(releases[0].url, tarball_url("3.1.1"));
+
+        let missing = "\
+name	url	sha1	sha512
+ruby-3.1.1	https://cache.ruby-lang.org/pub/ruby/3.1/ruby-3.1.1.tar.gz	289cbb9eae338bdaf99e376ac511236e39be83a3	a60d69d35d6d4ad8926b324a6092f962510183d9759b096ba4ce9db2e254e0f436030c2a62741352efe72aec5ca2329b45edd85cca8ad3254a9c57e3d8f66319
+";
+        assert!(matches!(
+            parse_data(missing),
+            Err(CheckerError::MissingColumn(c)) if c == "sha256"
+        ));
+        assert!(matches!(
+            parse_data_iter(missing.as_bytes()).next(),
+            Some(Err(CheckerError::MissingColumn(c))) if c == "sha256"
+        ));
+        assert_eq!(parse_data_iter(missing.as_bytes()).count(), 1);
+    }
+
+    #[test]
+    fn iterator_matches_eager_parse() {
+        let content = fs::read_to_string("test/fixtures/index.txt").unwrap();
+        let eager = parse_data(&content).unwrap();
+
+        let file = fs::File::open("test/fixtures/index.txt").unwrap();
+        let lazy: Vec<Release> = parse_data_iter(file).map(Result::unwrap).collect();
+
+        assert_eq!(lazy.len(), eager.len());
+        for (lazy, eager) in lazy.iter().zip(&eager) {
+            assert_eq!(lazy.version, eager.version);
+            assert_eq!(lazy.url, eager.url);
+            assert_eq!(lazy.sha256, eager.sha256);
+        }
+    }
+
+    #[test]
+    fn parse_one_line_correctly() {
         let line = "\
 name	url	sha1	sha256	sha512
 ruby-3.1.1	https://cache.ruby-lang.org/pub/ruby/3.1/ruby-3.1.1.tar.gz	289cbb9eae338bdaf99e376ac511236e39be83a3	fe6e4782de97443978ddba8ba4be38d222aa24dc3e3f02a6a8e7701c0eeb619d	a60d69d35d6d4ad8926b324a6092f962510183d9759b096ba4ce9db2e254e0f436030c2a62741352efe72aec5ca2329b45edd85cca8ad3254a9c57e3d8f66319
 ";
-        let releases = parse_data(line).await.unwrap();
+        let releases = parse_data(line).unwrap();
         let release = releases.first().unwrap();
         assert_eq!(
             release.version,
@@ -175,10 +1650,188 @@ ruby-3.1.1	https://cache.ruby-lang.org/pub/ruby/3.1/ruby-3.1.1.tar.gz	289cbb9eae
         )
     }
 
-    #[tokio::test]
-    async fn returns_latest_versions() {
+    #[test]
+    fn reads_patchless_versions_only_when_lenient() {
+        let line = "\
+name	url	sha1	sha256	sha512
+ruby-3.2	https://cache.ruby-lang.org/pub/ruby/3.2/ruby-3.2.tar.gz	670fce00d83771a1349b116e56a8a3b0ad323769	96c57558871a6748de5bc9f274e93f4b5aad06cd8f37befa0e8d94e7b8a423bc	bcc68f3f24c1c8987d9c80b57332e5791f25b935ba38daf5addf60dbfe3a05f9dcaf21909681b88e862c67c6ed103150f73259c6e35c564f13a00f432e3c1e46
+";
+        let strict = parse_report(line, &VersionFilter::default()).unwrap();
+        assert!(strict.releases.is_empty());
+        assert_eq!(strict.skipped[0].reason, SkipReason::BadSemVer);
+
+        let lenient = VersionFilter {
+            lenient_patch: true,
+            ..Default::default()
+        };
+        let releases = parse_data_with_filter(line, &lenient).unwrap();
+        assert_eq!(releases.len(), 1);
+        assert_eq!(releases[0].version, "3.2.0".parse().unwrap());
+
+        assert_eq!(normalize_patchless("ruby-3.2.2"), "ruby-3.2.2");
+        assert_eq!(
+            normalize_patchless("ruby-3.2-preview1"),
+            "ruby-3.2-preview1"
+        );
+        assert_eq!(normalize_patchless("jruby-9.4"), "jruby-9.4");
+    }
+
+    #[test]
+    fn parse_comma_delimited_line() {
+        let line = "\
+name,url,sha1,sha256,sha512
+ruby-3.1.1,https://cache.ruby-lang.org/pub/ruby/3.1/ruby-3.1.1.tar.gz,289cbb9eae338bdaf99e376ac511236e39be83a3,fe6e4782de97443978ddba8ba4be38d222aa24dc3e3f02a6a8e7701c0eeb619d,a60d69d35d6d4ad8926b324a6092f962510183d9759b096ba4ce9db2e254e0f436030c2a62741352efe72aec5ca2329b45edd85cca8ad3254a9c57e3d8f66319
+";
+        let filter = VersionFilter {
+            delimiter: b',',
+            ..Default::default()
+        };
+        let releases = parse_data_with_filter(line, &filter).unwrap();
+        assert_eq!(releases.len(), 1);
+        assert_eq!(releases[0].version, "3.1.1".parse().unwrap());
+
+        assert!(matches!(
+            parse_data(line),
+            Err(CheckerError::MissingColumn(c)) if c == "name"
+        ));
+    }
+
+    #[test]
+    fn skips_rows_without_ruby_prefix() {
+        let lines = "\
+name	url	sha1	sha256	sha512
+ruby-3.1.1	https://cache.ruby-lang.org/pub/ruby/3.1/ruby-3.1.1.tar.gz	289cbb9eae338bdaf99e376ac511236e39be83a3	fe6e4782de97443978ddba8ba4be38d222aa24dc3e3f02a6a8e7701c0eeb619d	a60d69d35d6d4ad8926b324a6092f962510183d9759b096ba4ce9db2e254e0f436030c2a62741352efe72aec5ca2329b45edd85cca8ad3254a9c57e3d8f66319
+jruby-9.4.0.0	https://cache.ruby-lang.org/pub/ruby/3.1/jruby-9.4.0.0.tar.gz	289cbb9eae338bdaf99e376ac511236e39be83a3	fe6e4782de97443978ddba8ba4be38d222aa24dc3e3f02a6a8e7701c0eeb619d	a60d69d35d6d4ad8926b324a6092f962510183d9759b096ba4ce9db2e254e0f436030c2a62741352efe72aec5ca2329b45edd85cca8ad3254a9c57e3d8f66319
+ruby-3.2.2	https://cache.ruby-lang.org/pub/ruby/3.2/ruby-3.2.2.tar.gz	670fce00d83771a1349b116e56a8a3b0ad323769	96c57558871a6748de5bc9f274e93f4b5aad06cd8f37befa0e8d94e7b8a423bc	bcc68f3f24c1c8987d9c80b57332e5791f25b935ba38daf5addf60dbfe3a05f9dcaf21909681b88e862c67c6ed103150f73259c6e35c564f13a00f432e3c1e46
+";
+        let releases = parse_data(lines).unwrap();
+        assert_eq!(releases.len(), 2);
+        assert_eq!(releases[0].version, "3.1.1".parse().unwrap());
+        assert_eq!(releases[1].version, "3.2.2".parse().unwrap());
+    }
+
+    #[cfg(feature = "logging")]
+    #[test]
+    #[tracing_test::traced_test]
+    fn logs_skipped_rows() {
+        let lines = "\
+name	url	sha1	sha256	sha512
+ruby-2.7.0	https://cache.ruby-lang.org/pub/ruby/2.7/ruby-2.7.0.tar.gz	sha1	sha256	sha512
+";
+        let releases = parse_data(lines).unwrap();
+
+        assert!(releases.is_empty());
+        assert!(logs_contain("Skipping invalid release"));
+        assert!(logs_contain("ruby-2.7.0"));
+        assert!(logs_contain("kept=0"));
+    }
+
+    #[cfg(feature = "logging")]
+    #[test]
+    #[tracing_test::traced_test]
+    fn logs_name_of_reordered_skipped_rows() {
+        let lines = "\
+url	name	sha256
+https://cache.ruby-lang.org/pub/ruby/2.7/ruby-2.7.0.tar.gz	ruby-2.7.0	sha256
+";
+        parse_data(lines).unwrap();
+
+        assert!(logs_contain("name=\"ruby-2.7.0\""));
+        assert!(!logs_contain("name=\"https://"));
+    }
+
+    #[test]
+    fn drops_duplicate_versions() {
+        let row = fs::read_to_string("test/fixtures/index.txt")
+            .unwrap()
+            .lines()
+            .find(|l| l.starts_with("ruby-3.2.2\t") && l.contains(".tar.gz\t"))
+            .unwrap()
+            .to_string();
+        let mirrored = row.replace("cache.ruby-lang.org", "mirror.example");
+        let lines = format!("name\turl\tsha1\tsha256\tsha512\n{row}\n{mirrored}\n");
+
+        let releases = parse_data(&lines).unwrap();
+        assert_eq!(releases.len(), 1);
+        assert!(releases[0].url.contains("cache.ruby-lang.org"));
+    }
+
+    #[test]
+    fn reports_skipped_rows() {
+        let content = fs::read_to_string("test/fixtures/index.txt").unwrap();
+        let mut lines: Vec<&str> = content.lines().take(1).collect();
+        lines.extend(content.lines().filter(|l| l.starts_with("ruby-3.2.2\t")));
+        let mirrored = lines[1].replace("https://", "http://");
+        lines.push(&mirrored);
+        lines.push("jruby-9.4.0.0\thttps://example.org/jruby-9.4.0.0.tar.gz\t-\t-\t-");
+        lines.push("ruby-0.51-0.52.diff.gz\thttps://example.org/ruby-0.51-0.52.diff.gz\t-\t-\t-");
+        lines.push("ruby-3.2.3\thttps://example.org/ruby-3.2.3.tar.gz\t-\t-\t-");
+        lines.push("ruby-2.7.8\thttps://example.org/ruby-2.7.8.tar.gz\t-\t-\t-");
+        let tsv = lines.join("\n");
+
+        let report = parse_report(&tsv, &VersionFilter::default()).unwrap();
+        assert_eq!(report.releases.len(), 1);
+        assert_eq!(report.releases[0].version, "3.2.2".parse().unwrap());
+
+        let reasons: Vec<SkipReason> = report.skipped.iter().map(|s| s.reason).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                SkipReason::Invalid(InvalidReason::BadUrl),
+                SkipReason::Invalid(InvalidReason::BadUrl),
+                SkipReason::Invalid(InvalidReason::BadUrl),
+                SkipReason::BadPrefix,
+                SkipReason::BadSemVer,
+                SkipReason::Invalid(InvalidReason::BadChecksum),
+                SkipReason::Invalid(InvalidReason::WrongMajor),
+            ]
+        );
+        assert_eq!(report.skipped[2].line, mirrored);
+
+        let duplicate = format!("{tsv}\n{}", lines[1]);
+        let report = parse_report(&duplicate, &VersionFilter::default()).unwrap();
+        assert_eq!(report.skipped.last().unwrap().reason, SkipReason::Duplicate);
+    }
+
+    #[test]
+    fn counts_total_and_kept_rows() {
+        let content = fs::read_to_string("test/fixtures/index.txt").unwrap();
+        let stats = parse_report(&content, &VersionFilter::default())
+            .unwrap()
+            .stats();
+
+        assert_eq!(
+            stats,
+            ParseStats {
+                total: 980,
+                kept: 9
+            }
+        );
+        assert_eq!(stats.to_string(), "parsed 980 rows, kept 9");
+    }
+
+    #[test]
+    fn merges_shards_without_duplicates() {
+        let mut releases = convert_to_versions(good_data());
+        let b = releases.split_off(3);
+        let mut a = releases;
+        let mut mirrored = b[0].clone();
+        mirrored.url = "https://mirror.internal/ruby-3.2.11.tar.gz".to_string();
+        a.push(mirrored);
+
+        let merged = merge(a, b);
+        let versions: Vec<String> = merged.iter().map(|r| r.version.to_string()).collect();
+        assert_eq!(
+            versions,
+            vec!["3.1.0", "3.1.12", "3.2.0", "3.2.2", "3.2.11", "3.3.0", "3.3.12"]
+        );
+        assert_eq!(merged[4].url, "https://mirror.internal/ruby-3.2.11.tar.gz");
+    }
+
+    #[test]
+    fn returns_latest_versions() {
         let releases = convert_to_versions(good_data());
-        let latest = latest_versions(releases).await;
+        let latest = latest_versions(releases);
         assert_eq!(latest.len(), 3);
         assert_eq!(latest[0].version.minor, 1);
         assert_eq!(latest[1].version.minor, 2);
@@ -188,30 +1841,412 @@ ruby-3.1.1	https://cache.ruby-lang.org/pub/ruby/3.1/ruby-3.1.1.tar.gz	289cbb9eae
         assert_eq!(latest[2].version.patch, 12);
     }
 
+    #[test]
+    fn partitions_latest_versions_by_major() {
+        let mut data = good_data();
+        for version in ["2.7.8", "2.7.10", "2.7.9", "2.7.11-preview1", "2.6.10"] {
+            data.push(Data {
+                version,
+                url: tarball_url(version),
+            })
+        }
+        let releases = convert_to_versions(data);
+
+        let two = latest_versions_for_major(releases.clone(), 2);
+        let versions: Vec<String> = two.iter().map(|r| r.version.to_string()).collect();
+        assert_eq!(versions, vec!["2.6.10", "2.7.10"]);
+
+        let three = latest_versions_for_major(releases, 3);
+        let versions: Vec<String> = three.iter().map(|r| r.version.to_string()).collect();
+        assert_eq!(versions, vec!["3.1.12", "3.2.11", "3.3.12"]);
+    }
+
+    #[test]
+    fn returns_all_valid_versions_sorted() {
+        let content = fs::read_to_string("test/fixtures/index.txt").unwrap();
+        let releases = parse_data(&content).unwrap();
+        let all = all_valid_sorted(releases);
+
+        assert_eq!(all.len(), 9);
+        assert!(all.windows(2).all(|w| w[0].version < w[1].version));
+        assert_eq!(all[0].version, "3.1.0".parse().unwrap());
+        assert_eq!(all[8].version, "3.3.0".parse().unwrap());
+    }
+
+    #[test]
+    fn excludes_prereleases_by_default() {
+        let content = fs::read_to_string("test/fixtures/index.txt").unwrap();
+        let releases = parse_data(&content).unwrap();
+
+        assert!(releases.iter().all(|r| r.version.pre.is_empty()));
+    }
+
+    #[test]
+    fn includes_prereleases_when_enabled() {
+        let filter = VersionFilter {
+            include_prereleases: true,
+            ..Default::default()
+        };
+        let content = fs::read_to_string("test/fixtures/index.txt").unwrap();
+        let releases = parse_data_with_filter(&content, &filter).unwrap();
+        assert!(releases
+            .iter()
+            .any(|r| r.version == "3.3.0-preview1".parse().unwrap()));
+
+        let latest = latest_versions(releases);
+        assert_eq!(latest[2].version, "3.3.0".parse().unwrap());
+
+        let mut data = good_data();
+        data.push(Data {
+            version: "3.4.0-preview1",
+            url: tarball_url("3.4.0-preview1"),
+        });
+        let releases = convert_to_versions(data);
+        assert!(releases.iter().all(|r| r.valid(&filter)));
+
+        let latest = latest_versions(releases);
+        assert_eq!(latest.len(), 4);
+        assert_eq!(latest[3].version, "3.4.0-preview1".parse().unwrap());
+        assert!(latest[3].version < "3.4.0".parse().unwrap());
+    }
+
+    #[test]
+    fn constrains_latest_versions_to_minor_range() {
+        let filter = VersionFilter {
+            minors: 0..2,
+            ..Default::default()
+        };
+        let releases = convert_to_versions(good_data());
+        assert!(!releases[2].valid(&filter));
+
+        let latest = latest_versions_with_filter(releases, &filter);
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].version, "3.1.12".parse().unwrap());
+    }
+
+    #[test]
+    fn sorts_valid_releases_newest_first() {
+        let mut data = good_data();
+        data.extend(bad_data());
+        let sorted = sorted_descending(convert_to_versions(data));
+
+        assert_eq!(sorted.len(), good_data().len());
+        assert_eq!(sorted[0].version, "3.3.12".parse().unwrap());
+        assert!(sorted.windows(2).all(|w| w[0] > w[1]));
+    }
+
+    #[test]
+    fn selects_releases_matching_requirement() {
+        let mut data = good_data();
+        data.extend(bad_data());
+        let req = VersionReq::parse(">=3.1, <3.3").unwrap();
+        let selected = matching(convert_to_versions(data), &req);
+
+        assert_eq!(selected.len(), 5);
+        assert!(selected.iter().all(|r| r.version.minor < 3));
+
+        let latest = latest_versions(selected);
+        let versions: Vec<String> = latest.iter().map(|r| r.version.to_string()).collect();
+        assert_eq!(versions, vec!["3.1.12", "3.2.11"]);
+    }
+
+    #[test]
+    fn resolves_wildcard_patterns() {
+        let mut data = good_data();
+        data.extend(bad_data());
+        let versions = convert_to_versions(data);
+        let resolve =
+            |pattern| resolve_wildcard(versions.clone(), pattern).map(|r| r.version.to_string());
+
+        assert_eq!(resolve("3.2.x").as_deref(), Some("3.2.11"));
+        assert_eq!(resolve("3.x").as_deref(), Some("3.3.12"));
+        assert_eq!(resolve("3.4.x"), None);
+        for pattern in ["3.2", "3.2.1.x", "x", "three.x", "3.2.*"] {
+            assert_eq!(resolve(pattern), None);
+        }
+    }
+
+    #[test]
+    fn trims_whitespace_around_fields() {
+        let fixture = fs::read_to_string("test/fixtures/index_crlf.txt").unwrap();
+        let versions: Vec<String> = parse_data(&fixture)
+            .unwrap()
+            .iter()
+            .map(|r| r.version.to_string())
+            .collect();
+
+        assert_eq!(versions, ["3.1.4", "3.2.2", "3.3.0"]);
+    }
+
+    #[test]
+    fn lists_available_minors() {
+        let mut data = good_data();
+        data.extend(bad_data());
+
+        assert_eq!(available_minors(&convert_to_versions(data)), vec![1, 2, 3]);
+        assert!(available_minors(&[]).is_empty());
+    }
+
+    #[test]
+    fn extracts_versions_only() {
+        let latest = latest_versions(convert_to_versions(good_data()));
+        let expected: Vec<SemVerVersion> = ["3.1.12", "3.2.11", "3.3.12"]
+            .iter()
+            .map(|v| v.parse().unwrap())
+            .collect();
+
+        assert_eq!(versions_only(&latest), expected);
+    }
+
+    #[test]
+    fn finds_exact_versions() {
+        let versions = convert_to_versions(good_data());
+
+        assert!(contains_version(&versions, &"3.2.2".parse().unwrap()));
+        assert!(!contains_version(&versions, &"3.2.3".parse().unwrap()));
+    }
+
+    #[test]
+    fn returns_releases_newer_than_current() {
+        let mut data = good_data();
+        data.extend(bad_data());
+        let newer = newer_than(convert_to_versions(data), &"3.1.0".parse().unwrap());
+
+        let versions: Vec<String> = newer.iter().map(|r| r.version.to_string()).collect();
+        assert_eq!(
+            versions,
+            vec!["3.1.12", "3.2.0", "3.2.2", "3.2.11", "3.3.0", "3.3.12"]
+        );
+    }
+
+    #[test]
+    fn limits_latest_versions_to_highest_minors() {
+        let mut data = good_data();
+        data.extend(bad_data());
+        let releases = convert_to_versions(data);
+
+        let latest = latest_n_minors(releases.clone(), 2);
+        let versions: Vec<String> = latest.iter().map(|r| r.version.to_string()).collect();
+        assert_eq!(versions, vec!["3.2.11", "3.3.12"]);
+
+        assert_eq!(latest_n_minors(releases.clone(), 10).len(), 3);
+        assert!(latest_n_minors(releases, 0).is_empty());
+    }
+
+    #[test]
+    fn returns_latest_for_minor() {
+        let releases = convert_to_versions(good_data());
+        let latest = latest_for_minor(releases, 2).unwrap();
+
+        assert_eq!(latest.version, "3.2.11".parse().unwrap());
+    }
+
+    #[test]
+    fn returns_none_for_missing_minor() {
+        let releases = convert_to_versions(good_data());
+
+        assert!(latest_for_minor(releases, 7).is_none());
+    }
+
+    #[test]
+    fn returns_newest_valid_release() {
+        let mut data = good_data();
+        data.extend(bad_data());
+        let newest = newest(convert_to_versions(data)).unwrap();
+
+        assert_eq!(newest.version, "3.3.12".parse().unwrap());
+    }
+
+    #[test]
+    fn groups_valid_releases_by_minor() {
+        let mut data = good_data();
+        data.extend(bad_data());
+        let groups = group_by_minor(convert_to_versions(data));
+
+        assert_eq!(groups.keys().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+        let versions: Vec<String> = groups[&2].iter().map(|r| r.version.to_string()).collect();
+        assert_eq!(versions, vec!["3.2.0", "3.2.2", "3.2.11"]);
+    }
+
+    #[test]
+    fn drops_versions_below_floor() {
+        let releases = convert_to_versions(good_data());
+        let latest = versions_at_least(releases, "3.2.5".parse().unwrap());
+        assert_eq!(latest.len(), 2);
+        assert_eq!(latest[0].version, "3.2.11".parse().unwrap());
+        assert_eq!(latest[1].version, "3.3.12".parse().unwrap());
+    }
+
+    #[test]
+    fn floor_is_inclusive() {
+        let releases = convert_to_versions(vec![Data {
+            version: "3.1.0",
+            url: tarball_url("3.1.0"),
+        }]);
+        let latest = versions_at_least(releases, "3.1.0".parse().unwrap());
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].version, "3.1.0".parse().unwrap());
+    }
+
+    #[test]
+    fn floor_with_missing_minor_returns_newer_series() {
+        let mut data = good_data();
+        data.retain(|d| !d.version.starts_with("3.2."));
+        let releases = convert_to_versions(data);
+        let latest = versions_at_least(releases, "3.2.0".parse().unwrap());
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].version, "3.3.12".parse().unwrap());
+    }
+
+    #[test]
+    fn latest_versions_matches_naive_implementation() {
+        let mut rng = rand::thread_rng();
+        let mut releases = vec![];
+        for i in 0..500 {
+            let version = SemVerVersion::new(3, rng.gen_range(0..8), rng.gen_range(0..40));
+            releases.push(Release::new(
+                version,
+                format!("https://mirror.example/{i}.tar.gz"),
+                good_sha256().to_string(),
+            ));
+        }
+
+        let expected = naive_latest_versions(releases.clone());
+        let latest = latest_versions(releases);
+
+        assert_eq!(latest.len(), expected.len());
+        for (latest, expected) in latest.iter().zip(&expected) {
+            assert_eq!(latest.version, expected.version);
+            assert_eq!(latest.url, expected.url);
+        }
+    }
+
+    // The original per-minor scan, kept as a reference for the single-pass version.
+    fn naive_latest_versions(versions: Vec<Release>) -> Vec<Release> {
+        let mut releases: Vec<Release> = vec![];
+        for number in MINOR_RANGE {
+            let mut v = versions.clone();
+            v.retain(|r| r.version.minor == number);
+            v.sort();
+            if let Some(r) = v.last() {
+                releases.push(r.to_owned())
+            }
+        }
+
+        releases
+    }
+
+    #[test]
+    fn reports_newer_patch_for_installed_minor() {
+        let latest = convert_to_versions(good_data());
+        let newer = is_outdated(&"3.2.2".parse().unwrap(), &latest).unwrap();
+
+        assert_eq!(newer.version, "3.2.11".parse().unwrap());
+    }
+
+    #[test]
+    fn up_to_date_install_is_not_outdated() {
+        let latest = convert_to_versions(good_data());
+
+        assert!(is_outdated(&"3.2.11".parse().unwrap(), &latest).is_none());
+    }
+
+    #[test]
+    fn unknown_minor_is_not_outdated() {
+        let latest = convert_to_versions(good_data());
+
+        assert!(is_outdated(&"3.0.1".parse().unwrap(), &latest).is_none());
+    }
+
+    #[test]
+    fn reports_dataset_issues() {
+        let fixture = fs::read_to_string("test/fixtures/index.txt").unwrap();
+        let parsed = parse_data(&fixture).unwrap();
+        assert!(validate_dataset(&parsed).is_empty());
+
+        let mut duplicated = parsed.clone();
+        duplicated.insert(1, parsed[0].clone());
+        assert_eq!(
+            validate_dataset(&duplicated),
+            vec![DatasetIssue::Duplicate(parsed[0].version.clone())]
+        );
+
+        let mut unordered = parsed.clone();
+        unordered.swap(0, 1);
+        assert_eq!(
+            validate_dataset(&unordered),
+            vec![DatasetIssue::OutOfOrder(parsed[0].version.clone())]
+        );
+
+        let invalid = Release::builder()
+            .version("3.4.0".parse().unwrap())
+            .url("https://example.com/ruby-3.4.0.tar.gz")
+            .sha256("")
+            .build();
+        assert_eq!(
+            validate_dataset(&[invalid]),
+            vec![DatasetIssue::Invalid("3.4.0".parse().unwrap())]
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_parse_matches_serial_parse() {
+        let fixture = fs::read_to_string("test/fixtures/index.txt").unwrap();
+        let mut lines = fixture.lines();
+        let mut data = lines.next().unwrap().to_string();
+        let rows: Vec<&str> = lines.collect();
+        // Repeat the fixture, with duplicates, and add thousands of
+        // synthetic releases on top.
+        for _ in 0..5 {
+            for row in &rows {
+                data.push('\n');
+                data.push_str(row);
+            }
+        }
+        for minor in MINOR_RANGE {
+            for patch in PATCH_RANGE {
+                let version = format!("3.{minor}.{patch}");
+                data.push_str(&format!(
+                    "\nruby-{version}\t{}\tsha1\t{}\tsha512",
+                    tarball_url(&version),
+                    good_sha256()
+                ));
+            }
+        }
+
+        let filter = VersionFilter::default();
+        let serial = parse_data_with_filter(&data, &filter).unwrap();
+        let parallel = parse_data_parallel(&data, &filter).unwrap();
+
+        assert!(serial.len() > 5000);
+        assert_eq!(parallel, serial);
+        assert!(parallel.iter().zip(&serial).all(|(p, s)| p.strict_eq(s)));
+    }
+
     fn convert_to_versions(data: Vec<Data>) -> Vec<Release> {
         let mut releases = vec![];
         for item in data {
-            releases.push(Release {
-                version: item.version.parse::<SemVerVersion>().unwrap(),
-                url: item.url.to_owned(),
-                sha256: "sha256".to_string(),
-            })
+            releases.push(
+                Release::builder()
+                    .version(item.version.parse().unwrap())
+                    .url(item.url)
+                    .sha256(good_sha256())
+                    .build(),
+            )
         }
         releases
     }
 
     fn good_data() -> Vec<Data> {
         let mut releases = vec![];
-        for (version, url) in &[
-            ("3.3.0", good_url()),
-            ("3.3.12", good_url()),
-            ("3.2.0", good_url()),
-            ("3.2.11", good_url()),
-            ("3.2.2", good_url()),
-            ("3.1.0", good_url()),
-            ("3.1.12", good_url()),
+        for version in [
+            "3.3.0", "3.3.12", "3.2.0", "3.2.11", "3.2.2", "3.1.0", "3.1.12",
         ] {
-            releases.push(Data { version, url })
+            releases.push(Data {
+                version,
+                url: tarball_url(version),
+            })
         }
 
         releases
@@ -219,13 +2254,13 @@ ruby-3.1.1	https://cache.ruby-lang.org/pub/ruby/3.1/ruby-3.1.1.tar.gz	289cbb9eae
 
     fn bad_data() -> Vec<Data> {
         let mut data = vec![];
-        for (version, url) in &[
-            ("2.7.0", one_bad_url()),
-            ("3.2.0-preview1", one_bad_url()),
-            ("3.2.0-rc2", one_bad_url()),
-            ("3.1.5-something", one_bad_url()),
-            ("3.0.5", good_url()),
-            ("3.0.16", good_url()),
+        for (version, url) in [
+            ("2.7.0", one_bad_url().to_string()),
+            ("3.2.0-preview1", one_bad_url().to_string()),
+            ("3.2.0-rc2", one_bad_url().to_string()),
+            ("3.1.5-something", one_bad_url().to_string()),
+            ("3.0.5", tarball_url("3.0.5")),
+            ("3.0.16", tarball_url("3.0.16")),
         ] {
             data.push(Data { version, url })
         }
@@ -236,12 +2271,24 @@ ruby-3.1.1	https://cache.ruby-lang.org/pub/ruby/3.1/ruby-3.1.1.tar.gz	289cbb9eae
         let mut data = bad_data();
         data.push(Data {
             version: "3.2.0",
-            url: one_bad_url(),
+            url: one_bad_url().to_string(),
         });
 
         data
     }
 
+    fn good_sha256() -> &'static str {
+        "96c57558871a6748de5bc9f274e93f4b5aad06cd8f37befa0e8d94e7b8a423bc"
+    }
+
+    fn tarball_url(version: &str) -> String {
+        let series: Vec<&str> = version.split('.').take(2).collect();
+        format!(
+            "https://cache.ruby-lang.org/pub/ruby/{}/ruby-{version}.tar.gz",
+            series.join(".")
+        )
+    }
+
     fn good_url() -> &'static str {
         "https://cache.ruby-lang.org/pub/ruby/3.1/ruby-3.1.4.tar.gz"
     }
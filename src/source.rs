@@ -0,0 +1,186 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::NaiveDate;
+use reqwest::{Method, Request, Url};
+use semver::Version as SemVerVersion;
+use serde::Deserialize;
+
+use crate::client::{fetch_data, HttpClient};
+use crate::error::CheckerError;
+use crate::release::{parse_data_with_filter, Release, VersionFilter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SourceFormat {
+    #[default]
+    IndexTxt,
+    ReleasesJson,
+}
+
+impl SourceFormat {
+    pub fn parse(&self, body: &str, filter: &VersionFilter) -> Result<Vec<Release>, CheckerError> {
+        match self {
+            SourceFormat::IndexTxt => parse_data_with_filter(body, filter),
+            SourceFormat::ReleasesJson => parse_releases_json_with_filter(body, filter),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct JsonRelease {
+    version: String,
+    date: Option<String>,
+    url: Archives,
+    sha256: Archives,
+}
+
+#[derive(Deserialize)]
+struct Archives {
+    gz: Option<String>,
+}
+
+pub fn parse_releases_json(body: &str) -> Result<Vec<Release>, CheckerError> {
+    parse_releases_json_with_filter(body, &VersionFilter::default())
+}
+
+/// Parses the structured release list, keeping the tar.gz archive of every
+/// valid release. Entries without one, or with an unparseable version, are
+/// skipped like unparseable rows in the index. As with the index, the result
+/// is sorted and duplicate versions keep their first entry.
+pub fn parse_releases_json_with_filter(
+    body: &str,
+    filter: &VersionFilter,
+) -> Result<Vec<Release>, CheckerError> {
+    let entries: Vec<JsonRelease> = serde_json::from_str(body)?;
+
+    let mut result = vec![];
+    let mut seen = HashSet::new();
+    for entry in entries {
+        let (Some(url), Some(sha256)) = (entry.url.gz, entry.sha256.gz) else {
+            continue;
+        };
+        let Ok(version) = entry.version.parse() else {
+            continue;
+        };
+        let release = Release::new(version, url, sha256);
+        if release.valid(filter) && seen.insert(release.version().clone()) {
+            result.push(release)
+        }
+    }
+    result.sort();
+    Ok(result)
+}
+
+/// Release dates by version, from the structured release list. Entries
+/// without a parseable version or date are left out.
+pub fn parse_release_dates(body: &str) -> Result<HashMap<SemVerVersion, NaiveDate>, CheckerError> {
+    let entries: Vec<JsonRelease> = serde_json::from_str(body)?;
+    Ok(entries
+        .into_iter()
+        .filter_map(|entry| {
+            let version = entry.version.parse().ok()?;
+            let date = entry.date?.parse().ok()?;
+            Some((version, date))
+        })
+        .collect())
+}
+
+/// Fetches the release list at `url` and attaches its release dates to the
+/// matching releases. Releases it doesn't list are returned unchanged.
+pub async fn enrich_dates<C>(
+    releases: Vec<Release>,
+    url: Url,
+    client: &mut C,
+) -> Result<Vec<Release>, CheckerError>
+where
+    C: HttpClient,
+{
+    let body = fetch_data(Request::new(Method::GET, url), client).await?;
+    let dates = parse_release_dates(&body)?;
+    Ok(releases
+        .into_iter()
+        .map(|release| match dates.get(release.version()) {
+            Some(date) => release.with_released_on(*date),
+            None => release,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::release::parse_data;
+    use crate::test_util::FixtureClient;
+
+    use std::fs;
+
+    #[tokio::test]
+    async fn enriches_releases_with_dates() {
+        let index = fs::read_to_string("test/fixtures/index.txt").unwrap();
+        let mut releases = parse_data(&index).unwrap();
+        releases.push(Release::builder().version("3.9.0".parse().unwrap()).build());
+        let url = Url::parse("https://mirror.internal/releases.json").unwrap();
+
+        let releases = enrich_dates(
+            releases,
+            url,
+            &mut FixtureClient::new("test/fixtures/releases.json"),
+        )
+        .await
+        .unwrap();
+
+        let find = |v: &str| releases.iter().find(|r| r.version().to_string() == v);
+        assert_eq!(
+            find("3.2.2").unwrap().released_on(),
+            NaiveDate::from_ymd_opt(2023, 3, 30)
+        );
+        assert_eq!(find("3.9.0").unwrap().released_on(), None);
+    }
+
+    #[test]
+    fn releases_json_matches_index() {
+        let index = fs::read_to_string("test/fixtures/index.txt").unwrap();
+        let json = fs::read_to_string("test/fixtures/releases.json").unwrap();
+
+        let filter = VersionFilter::default();
+        let from_index = SourceFormat::IndexTxt.parse(&index, &filter).unwrap();
+        let from_json = SourceFormat::ReleasesJson.parse(&json, &filter).unwrap();
+
+        assert_eq!(from_json.len(), 9);
+        assert_eq!(from_json.len(), from_index.len());
+        assert!(from_json
+            .iter()
+            .zip(&from_index)
+            .all(|(j, i)| j.strict_eq(i)));
+    }
+
+    #[test]
+    fn applies_filter_to_releases_json() {
+        let json = fs::read_to_string("test/fixtures/releases.json").unwrap();
+        let filter = VersionFilter {
+            minors: 2..3,
+            ..Default::default()
+        };
+        let releases = parse_releases_json_with_filter(&json, &filter).unwrap();
+
+        assert!(!releases.is_empty());
+        assert!(releases.iter().all(|r| r.version().minor == 2));
+        assert!(releases.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn skips_invalid_releases() {
+        let json = fs::read_to_string("test/fixtures/releases.json").unwrap();
+        let releases = parse_releases_json(&json).unwrap();
+
+        assert_eq!(releases.len(), 9);
+        assert!(releases.iter().all(|r| r.version().major == 3));
+        assert!(releases.iter().all(|r| r.version().pre.is_empty()));
+    }
+
+    #[test]
+    fn rejects_malformed_json() {
+        let result = parse_releases_json("name\turl");
+
+        assert!(matches!(result, Err(CheckerError::Json(_))));
+    }
+}
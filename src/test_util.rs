@@ -0,0 +1,49 @@
+//! Test doubles shared by the unit tests of the library and the binary,
+//! which includes this file through `#[path]`.
+
+use std::fs;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use http::Response as HttpResponse;
+use reqwest::{Request, Response};
+
+use super::client::HttpClient;
+use super::error::CheckerError;
+
+/// Answers every request with the same fixture from `test/fixtures` and
+/// counts how often it was asked. Clones share the counter, so a test can
+/// hand one to the code under test and inspect the other.
+#[derive(Clone)]
+pub struct FixtureClient {
+    path: &'static str,
+    calls: Arc<AtomicU32>,
+}
+
+impl FixtureClient {
+    pub fn new(path: &'static str) -> Self {
+        Self {
+            path,
+            calls: Arc::default(),
+        }
+    }
+
+    /// Serves `test/fixtures/index.txt`.
+    pub fn index() -> Self {
+        Self::new("test/fixtures/index.txt")
+    }
+
+    pub fn calls(&self) -> u32 {
+        self.calls.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl HttpClient for FixtureClient {
+    async fn send_request(&mut self, _request: Request) -> Result<Response, CheckerError> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        let content = fs::read_to_string(self.path).unwrap();
+        Ok(Response::from(HttpResponse::new(content)))
+    }
+}